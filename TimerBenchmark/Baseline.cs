@@ -0,0 +1,51 @@
+﻿using System.Text.Json;
+
+namespace TimerBenchmark;
+
+internal static class Baseline
+{
+    // Returns false when the current optimal delta regressed past the threshold relative to the baseline
+    public static async Task<bool> CheckAsync(BenchmarkReport report, string path, double failThresholdPercent)
+    {
+        if (report.Optimal is not { } optimal)
+        {
+            await Console.Error.WriteLineAsync("error: no valid results to compare against the baseline");
+            return false;
+        }
+
+        if (!File.Exists(path))
+        {
+            await File.WriteAllTextAsync(path, report.ToJson());
+            Console.WriteLine($"info: no baseline found, saved this run as the baseline in {path}");
+            return true;
+        }
+
+        double baselineDelta;
+        try
+        {
+            using JsonDocument document = JsonDocument.Parse(await File.ReadAllTextAsync(path));
+            baselineDelta = document.RootElement.GetProperty("optimal").GetProperty("deltaMs").GetDouble();
+        }
+        catch (Exception e) when (e is JsonException or KeyNotFoundException or InvalidOperationException)
+        {
+            await Console.Error.WriteLineAsync($"error: {path} is not a valid benchmark report: {e.Message}");
+            return false;
+        }
+
+        double changePercent = baselineDelta > 0
+            ? (optimal.DeltaMs - baselineDelta) / baselineDelta * 100
+            : optimal.DeltaMs > baselineDelta ? double.PositiveInfinity : 0;
+
+        Console.WriteLine(
+            $"info: optimal delta {NumberFormat.Round(optimal.DeltaMs)}ms vs baseline {NumberFormat.Round(baselineDelta)}ms ({changePercent:+0.##;-0.##;0}%)");
+
+        if (changePercent > failThresholdPercent)
+        {
+            await Console.Error.WriteLineAsync(
+                $"error: optimal delta regressed by more than {failThresholdPercent}% compared to {path}");
+            return false;
+        }
+
+        return true;
+    }
+}
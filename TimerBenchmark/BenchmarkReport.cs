@@ -0,0 +1,550 @@
+﻿using System.Globalization;
+using System.Text;
+using System.Text.Json;
+
+namespace TimerBenchmark;
+
+internal sealed class BenchmarkReport
+{
+    private const string CsvHeader = "RequestedResolutionMs,DeltaMs,STDEV";
+
+    // Position of the delta cell in a table line: after the 16 wide resolution column and " | "
+    private const int DeltaCellStart = 19;
+    private const int DeltaCellWidth = 12;
+
+    // ISO 8601 local time with its UTC offset, so rows line up with event logs from the same machine
+    private const string TimestampFormat = "yyyy-MM-ddTHH:mm:sszzz";
+
+    // Tracked by position so rows with identical values are never both marked optimal
+    private readonly int? _optimalIndex;
+
+    private readonly OptimalMetric _metric;
+
+    public BenchmarkReport(IReadOnlyList<BenchmarkResult> results, OptimalMetric metric = OptimalMetric.Mean)
+    {
+        Results = results;
+        _metric = metric;
+        _optimalIndex = FindOptimalIndex(results, metric);
+    }
+
+    public IReadOnlyList<BenchmarkResult> Results { get; }
+    public BenchmarkResult? Optimal => _optimalIndex is { } index ? Results[index] : null;
+    public int? OptimalIndex => _optimalIndex;
+
+    // Difference between the worst and best delta, i.e. how much the choice of resolution matters on this system
+    public double? Spread => Results.Count > 0 ? Results.Max(r => r.DeltaMs) - Results.Min(r => r.DeltaMs) : null;
+
+    // Process named by --load-process and whether it was running during the sweep
+    public (string Name, bool Running)? LoadProcess { get; init; }
+
+    // CPU and Windows build the run was measured on, so a saved JSON report describes the run on its own
+    public (string Cpu, int WindowsBuild)? Machine { get; init; }
+
+    // Adds the exact 100ns unit count passed to the timer API next to the rounded ms value
+    public bool IncludeUnits { get; init; }
+
+    // Combines several runs per resolution, weighting each run by its sample count: the delta is the weighted mean of
+    // the run deltas and the STDEV is pooled, including the spread between runs. Results files written before the
+    // Samples column existed do not record a count, so a resolution with such a row weights every run equally.
+    // Optional columns are combined over the runs that recorded them: the median of the medians, the lowest minimum
+    // and highest maximum, the mean of the timer columns, throttled if any run was, and the latest timestamp
+    public static BenchmarkReport Merge(IReadOnlyList<IReadOnlyList<BenchmarkResult>> runs)
+    {
+        List<BenchmarkResult> merged = runs
+            .SelectMany(run => run)
+            .GroupBy(result => result.Resolution)
+            .OrderBy(group => group.Key)
+            .Select(group =>
+            {
+                bool counted = group.All(r => r.Samples.HasValue);
+                double Weight(BenchmarkResult r) => counted ? r.Samples!.Value : 1;
+
+                double totalWeight = group.Sum(Weight);
+                double delta = group.Sum(r => Weight(r) * r.DeltaMs) / totalWeight;
+                double stdev = Math.Sqrt(group.Sum(r => Weight(r) * (r.Stdev * r.Stdev + Math.Pow(r.DeltaMs - delta, 2)))
+                                         / totalWeight);
+                return new BenchmarkResult(group.First().ResolutionMs, group.Key, delta, stdev,
+                    AverageOrNull(group.Select(r => r.HighResTimerDeltaMs)),
+                    AverageOrNull(group.Select(r => r.TickPeriodMs)),
+                    AverageOrNull(group.Select(r => r.TickJitterMs)),
+                    group.Any(r => r.Throttled),
+                    group.Max(r => r.MeasuredAt),
+                    MedianOrNull(group.Select(r => r.MedianMs)),
+                    group.Min(r => r.MinMs),
+                    group.Max(r => r.MaxMs),
+                    counted ? group.Sum(r => r.Samples) : null);
+            })
+            .ToList();
+
+        return new BenchmarkReport(merged);
+    }
+
+    // Refined rows replace their noisy originals, every other row is kept as it was. Rows sharing a 100ns unit, e.g.
+    // from a --no-clamp results file, all take the last measurement of that unit
+    public static List<BenchmarkResult> ReplaceRefined(IReadOnlyList<BenchmarkResult> previous,
+        IReadOnlyList<BenchmarkResult> refined)
+    {
+        Dictionary<int, BenchmarkResult> latest = refined
+            .GroupBy(r => r.Resolution)
+            .ToDictionary(group => group.Key, group => group.Last());
+
+        return previous
+            .Select(row => latest.GetValueOrDefault(row.Resolution, row))
+            .ToList();
+    }
+
+    private static double? AverageOrNull(IEnumerable<double?> values)
+    {
+        List<double> present = values.OfType<double>().ToList();
+        return present.Count > 0 ? present.Average() : null;
+    }
+
+    private static double? MedianOrNull(IEnumerable<double?> values)
+    {
+        List<double> sorted = values.OfType<double>().Order().ToList();
+        if (sorted.Count == 0)
+        {
+            return null;
+        }
+
+        int middle = sorted.Count / 2;
+        return sorted.Count % 2 == 1 ? sorted[middle] : (sorted[middle - 1] + sorted[middle]) / 2;
+    }
+
+    // Reads back rows written by ToCsv, skipping anything that does not parse. Optional columns are found by their
+    // header name, so rewriting results.txt in place keeps them
+    public static List<BenchmarkResult> ParseCsv(string csv)
+    {
+        List<BenchmarkResult> results = new List<BenchmarkResult>();
+        Dictionary<string, int> columns = new Dictionary<string, int>();
+
+        foreach (string line in csv.Split('\n', StringSplitOptions.RemoveEmptyEntries | StringSplitOptions.TrimEntries))
+        {
+            string[] fields = line.Split(',', StringSplitOptions.TrimEntries);
+
+            if (line.StartsWith(CsvHeader, StringComparison.Ordinal))
+            {
+                columns = fields.Select((name, index) => (name, index)).ToDictionary(c => c.name, c => c.index);
+            }
+            else if (fields.Length >= 3
+                     && double.TryParse(fields[0], out double resolutionMs)
+                     && double.TryParse(fields[1], out double deltaMs)
+                     && double.TryParse(fields[2], out double stdev))
+            {
+                DateTimeOffset? measuredAt =
+                    columns.TryGetValue("Timestamp", out int index) && index < fields.Length
+                    && DateTimeOffset.TryParseExact(fields[index], TimestampFormat, CultureInfo.InvariantCulture,
+                        DateTimeStyles.None, out DateTimeOffset parsed)
+                        ? parsed
+                        : null;
+
+                results.Add(new BenchmarkResult(resolutionMs, (int)Math.Round(resolutionMs * 1E4), deltaMs, stdev,
+                    OptionalField(fields, columns, "HighResTimerDeltaMs"),
+                    OptionalField(fields, columns, "TickPeriodMs"),
+                    OptionalField(fields, columns, "TickJitterMs"),
+                    OptionalField(fields, columns, "Throttled") > 0,
+                    measuredAt,
+                    MedianMs: OptionalField(fields, columns, "MedianMs"),
+                    MinMs: OptionalField(fields, columns, "MinMs"),
+                    MaxMs: OptionalField(fields, columns, "MaxMs"),
+                    Samples: OptionalField(fields, columns, "Samples") is { } samples ? (int)samples : null));
+            }
+        }
+
+        return results;
+    }
+
+    private static double? OptionalField(string[] fields, Dictionary<string, int> columns, string name)
+    {
+        return columns.TryGetValue(name, out int index) && index < fields.Length
+               && double.TryParse(fields[index], out double value)
+            ? value
+            : null;
+    }
+
+    public string ToCsv()
+    {
+        List<OptionalColumn> columns = OptionalColumns();
+        bool timestamps = Results.Any(r => r.MeasuredAt.HasValue);
+        StringBuilder csv = new StringBuilder(CsvHeader);
+        foreach (OptionalColumn column in columns)
+        {
+            csv.Append(',').Append(column.CsvName);
+        }
+
+        if (timestamps)
+        {
+            csv.Append(",Timestamp");
+        }
+
+        csv.Append(Environment.NewLine);
+
+        foreach (BenchmarkResult result in Results)
+        {
+            // Merge and --refine-noisy read this file back, so values keep full round-trip precision
+            csv.Append($"{result.ResolutionMs:R}, {result.DeltaMs:R}, {result.Stdev:R}");
+            foreach (OptionalColumn column in columns)
+            {
+                csv.Append($", {column.Value(result)?.ToString("R")}");
+            }
+
+            if (timestamps)
+            {
+                csv.Append($", {result.MeasuredAt?.ToString(TimestampFormat, CultureInfo.InvariantCulture)}");
+            }
+
+            csv.Append(Environment.NewLine);
+        }
+
+        return csv.ToString();
+    }
+
+    public string ToTable(SortOrder sortBy = SortOrder.Resolution, double optimalBand = 0)
+    {
+        StringBuilder table = new StringBuilder();
+
+        foreach ((string line, _, _) in TableLines(sortBy, optimalBand))
+        {
+            table.AppendLine(line);
+        }
+
+        return table.ToString();
+    }
+
+    // Optimal row in green, rows within the optimal band in cyan, and each delta colored by its quality
+    public void PrintTable(SortOrder sortBy = SortOrder.Resolution, double optimalBand = 0,
+        DeltaThresholds? thresholds = null)
+    {
+        foreach ((string line, RowKind kind, double? delta) in TableLines(sortBy, optimalBand))
+        {
+            ConsoleColor rowColor = kind switch
+            {
+                RowKind.Optimal => ConsoleColor.Green,
+                RowKind.WithinBand => ConsoleColor.Cyan,
+                _ => Console.ForegroundColor
+            };
+            Console.ForegroundColor = rowColor;
+
+            if (delta is { } value && thresholds is { } quality)
+            {
+                Console.Write(line[..DeltaCellStart]);
+                Console.ForegroundColor = quality.ColorOf(value);
+                Console.Write(line.Substring(DeltaCellStart, DeltaCellWidth));
+                Console.ForegroundColor = rowColor;
+                Console.WriteLine(line[(DeltaCellStart + DeltaCellWidth)..]);
+            }
+            else
+            {
+                Console.WriteLine(line);
+            }
+
+            Console.ResetColor();
+        }
+    }
+
+    public string ToJson()
+    {
+        using MemoryStream buffer = new MemoryStream();
+        using (Utf8JsonWriter json = new Utf8JsonWriter(buffer, new JsonWriterOptions { Indented = true }))
+        {
+            json.WriteStartObject();
+
+            json.WriteStartArray("results");
+            foreach (BenchmarkResult result in Results)
+            {
+                WriteResult(json, result);
+            }
+
+            json.WriteEndArray();
+
+            json.WritePropertyName("optimal");
+            if (Optimal is { } optimal)
+            {
+                WriteResult(json, optimal);
+            }
+            else
+            {
+                json.WriteNullValue();
+            }
+
+            if (Spread is { } spread)
+            {
+                json.WriteNumber("spreadMs", spread);
+            }
+            else
+            {
+                json.WriteNull("spreadMs");
+            }
+
+            if (LoadProcess is { } loadProcess)
+            {
+                json.WriteStartObject("loadProcess");
+                json.WriteString("name", loadProcess.Name);
+                json.WriteBoolean("running", loadProcess.Running);
+                json.WriteEndObject();
+            }
+
+            if (Machine is { } machine)
+            {
+                json.WriteStartObject("system");
+                json.WriteString("cpu", machine.Cpu);
+                json.WriteNumber("windowsBuild", machine.WindowsBuild);
+                json.WriteEndObject();
+            }
+
+            json.WriteEndObject();
+        }
+
+        return Encoding.UTF8.GetString(buffer.ToArray());
+    }
+
+    // Replays the optimal selection and records every comparison, so the choice can be audited
+    public string ToOptimalTraceJson()
+    {
+        using MemoryStream buffer = new MemoryStream();
+        using (Utf8JsonWriter json = new Utf8JsonWriter(buffer, new JsonWriterOptions { Indented = true }))
+        {
+            json.WriteStartObject();
+            json.WriteString("rule",
+                $"lowest {MetricName(_metric)} wins, ties are broken by the lower STDEV, remaining ties keep the earlier row");
+
+            json.WriteStartArray("steps");
+            FindOptimalIndex(Results, _metric, (index, best, decision) =>
+            {
+                json.WriteStartObject();
+                json.WritePropertyName("candidate");
+                WriteResult(json, Results[index]);
+                if (best is { } bestIndex)
+                {
+                    json.WriteNumber(_metric == OptimalMetric.Median ? "minMedianMs" : "minDeltaMs",
+                        MetricValue(Results[bestIndex], _metric));
+                    json.WriteNumber("minStdev", Results[bestIndex].Stdev);
+                }
+
+                json.WriteString("decision", decision);
+                json.WriteEndObject();
+            });
+            json.WriteEndArray();
+
+            json.WritePropertyName("optimal");
+            if (Optimal is { } optimal)
+            {
+                WriteResult(json, optimal);
+            }
+            else
+            {
+                json.WriteNullValue();
+            }
+
+            json.WriteEndObject();
+        }
+
+        return Encoding.UTF8.GetString(buffer.ToArray());
+    }
+
+    public IEnumerable<(string Line, RowKind Kind, double? DeltaMs)> TableLines(SortOrder sortBy, double optimalBand)
+    {
+        List<OptionalColumn> columns = OptionalColumns().Where(c => c.Header is not null).ToList();
+        yield return ($"{"Resolution (ms)",16} | {"Delta (ms)",12} | {"STDEV",12}{string.Concat(columns.Select(c => $" | {c.Header,13}"))}",
+            RowKind.Header, null);
+        yield return ($"{new string('-', 16)}-+-{new string('-', 12)}-+-{new string('-', 12)}{string.Concat(columns.Select(_ => $"-+-{new string('-', 13)}"))}",
+            RowKind.Header, null);
+
+        IEnumerable<int> rows = Enumerable.Range(0, Results.Count);
+        rows = sortBy switch
+        {
+            SortOrder.Delta => rows.OrderBy(i => Results[i].DeltaMs).ThenBy(i => Results[i].Stdev),
+            SortOrder.Stdev => rows.OrderBy(i => Results[i].Stdev).ThenBy(i => Results[i].DeltaMs),
+            _ => rows
+        };
+
+        foreach (int i in rows)
+        {
+            BenchmarkResult result = Results[i];
+            RowKind kind = i == _optimalIndex
+                ? RowKind.Optimal
+                : optimalBand > 0 && Optimal is { } optimal
+                    && MetricValue(result, _metric) - MetricValue(optimal, _metric) <= optimalBand
+                    ? RowKind.WithinBand
+                    : RowKind.Result;
+            string marker = kind switch
+            {
+                RowKind.Optimal => "  <- optimal",
+                RowKind.WithinBand => $"  <- within {optimalBand}ms",
+                _ => string.Empty
+            };
+
+            string optional = string.Concat(columns.Select(c => $" | {c.Value(result)?.ToString(c.Format ?? NumberFormat.Fixed),13}"));
+            string throttled = result.Throttled ? "  [throttled]" : string.Empty;
+            yield return ($"{result.ResolutionMs,16:F4} | {result.DeltaMs.ToString(NumberFormat.Fixed),DeltaCellWidth} | {result.Stdev.ToString(NumberFormat.Fixed),12}{optional}{marker}{throttled}", kind, result.DeltaMs);
+        }
+    }
+
+    // Measurements enabled by a flag only get a column when at least one row has them
+    private List<OptionalColumn> OptionalColumns()
+    {
+        List<OptionalColumn> columns = new List<OptionalColumn>();
+
+        if (IncludeUnits)
+        {
+            columns.Add(new OptionalColumn("RequestedResolution100ns", "100ns units", r => r.Resolution, "F0"));
+        }
+
+        if (Results.Any(r => r.HighResTimerDeltaMs.HasValue))
+        {
+            columns.Add(new OptionalColumn("HighResTimerDeltaMs", "HR timer (ms)", r => r.HighResTimerDeltaMs));
+        }
+
+        if (Results.Any(r => r.MedianMs.HasValue))
+        {
+            columns.Add(new OptionalColumn("MedianMs", "Median (ms)", r => r.MedianMs));
+            columns.Add(new OptionalColumn("MinMs", "Min (ms)", r => r.MinMs));
+            columns.Add(new OptionalColumn("MaxMs", "Max (ms)", r => r.MaxMs));
+        }
+
+        if (Results.Any(r => r.TickPeriodMs.HasValue))
+        {
+            columns.Add(new OptionalColumn("TickPeriodMs", "Tick (ms)", r => r.TickPeriodMs));
+            columns.Add(new OptionalColumn("TickJitterMs", "Tick jitter", r => r.TickJitterMs));
+        }
+
+        // The table tags throttled rows instead, so like the sample count this is only written to the CSV
+        if (Results.Any(r => r.Throttled))
+        {
+            columns.Add(new OptionalColumn("Throttled", null, r => r.Throttled ? 1 : 0, "F0"));
+        }
+
+        // Only needed to weight the row when runs are merged, so it is kept out of the table
+        if (Results.Any(r => r.Samples.HasValue))
+        {
+            columns.Add(new OptionalColumn("Samples", null, r => r.Samples, "F0"));
+        }
+
+        return columns;
+    }
+
+    // A column without a Header is only written to the CSV
+    private readonly record struct OptionalColumn(string CsvName, string? Header, Func<BenchmarkResult, double?> Value,
+        string? Format = null);
+
+    // Baselines and the HPET comparison read reports back, so --precision only rounds what is printed
+    private static void WriteResult(Utf8JsonWriter json, BenchmarkResult result)
+    {
+        json.WriteStartObject();
+        json.WriteNumber("resolutionMs", result.ResolutionMs);
+        json.WriteNumber("deltaMs", result.DeltaMs);
+        json.WriteNumber("stdev", result.Stdev);
+        if (result.HighResTimerDeltaMs is { } highResTimerDelta)
+        {
+            json.WriteNumber("highResTimerDeltaMs", highResTimerDelta);
+        }
+
+        if (result is { MedianMs: { } median, MinMs: { } min, MaxMs: { } max })
+        {
+            json.WriteNumber("medianMs", median);
+            json.WriteNumber("minMs", min);
+            json.WriteNumber("maxMs", max);
+        }
+
+        if (result.Throttled)
+        {
+            json.WriteBoolean("throttled", true);
+        }
+
+        if (result is { TickPeriodMs: { } tickPeriod, TickJitterMs: { } tickJitter })
+        {
+            json.WriteNumber("tickPeriodMs", tickPeriod);
+            json.WriteNumber("tickJitterMs", tickJitter);
+        }
+
+        if (result.MeasuredAt is { } measuredAt)
+        {
+            json.WriteString("timestamp", measuredAt.ToString(TimestampFormat, CultureInfo.InvariantCulture));
+        }
+
+        json.WriteEndObject();
+    }
+
+    // Lowest delta (or median) wins; the steadier of two equal values is preferred. Rows without a median, e.g. read
+    // back from a results file, are compared by their delta
+    private static int? FindOptimalIndex(IReadOnlyList<BenchmarkResult> results, OptimalMetric metric,
+        Action<int, int?, string>? trace = null)
+    {
+        double Key(BenchmarkResult result) => MetricValue(result, metric);
+
+        string name = MetricName(metric);
+        int? optimal = null;
+
+        for (int i = 0; i < results.Count; i++)
+        {
+            string decision;
+
+            if (optimal is not { } best)
+            {
+                decision = "first candidate";
+            }
+            else if (Key(results[i]) < Key(results[best]))
+            {
+                decision = $"lower {name}";
+            }
+            else if (Key(results[i]) == Key(results[best]) && results[i].Stdev < results[best].Stdev)
+            {
+                decision = $"equal {name}, lower STDEV";
+            }
+            else
+            {
+                trace?.Invoke(i, optimal, Key(results[i]) == Key(results[best])
+                    ? $"rejected: equal {name}, STDEV not lower"
+                    : $"rejected: higher {name}");
+                continue;
+            }
+
+            trace?.Invoke(i, optimal, decision);
+            optimal = i;
+        }
+
+        return optimal;
+    }
+
+    private static double MetricValue(BenchmarkResult result, OptimalMetric metric)
+    {
+        return metric == OptimalMetric.Median && result.MedianMs is { } median ? median : result.DeltaMs;
+    }
+
+    private static string MetricName(OptimalMetric metric)
+    {
+        return metric == OptimalMetric.Median ? "median" : "delta";
+    }
+}
+
+internal enum OptimalMetric
+{
+    Mean,
+    Median
+}
+
+internal enum RowKind
+{
+    Header,
+    Result,
+    Optimal,
+    WithinBand
+}
+
+// Deltas below Good are shown in green, up to Fair in yellow and above it in red
+internal readonly record struct DeltaThresholds(double Good, double Fair)
+{
+    public static readonly DeltaThresholds Default = new DeltaThresholds(1.0, 2.0);
+
+    public ConsoleColor ColorOf(double deltaMs)
+    {
+        return deltaMs < Good ? ConsoleColor.Green : deltaMs <= Fair ? ConsoleColor.Yellow : ConsoleColor.Red;
+    }
+}
+
+internal enum SortOrder
+{
+    Resolution,
+    Delta,
+    Stdev
+}
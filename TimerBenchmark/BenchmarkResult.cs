@@ -0,0 +1,6 @@
+﻿namespace TimerBenchmark;
+
+internal readonly record struct BenchmarkResult(double ResolutionMs, int Resolution, double DeltaMs, double Stdev,
+    double? HighResTimerDeltaMs = null, double? TickPeriodMs = null, double? TickJitterMs = null,
+    bool Throttled = false, DateTimeOffset? MeasuredAt = null, double? MedianMs = null, double? MinMs = null,
+    double? MaxMs = null, int? Samples = null);
@@ -0,0 +1,13 @@
+﻿namespace TimerBenchmark;
+
+// The BenchmarkingParameters section of the configuration, after --start, --increment, --end and --samples are applied
+internal sealed class BenchmarkingParameters
+{
+    public double StartValue { get; init; }
+    public double IncrementValue { get; init; }
+    public double EndValue { get; init; }
+    public int SampleValue { get; init; }
+
+    // Specific resolutions to test instead of the StartValue/IncrementValue/EndValue range
+    public double[]? ResolutionList { get; init; }
+}
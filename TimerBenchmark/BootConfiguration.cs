@@ -0,0 +1,107 @@
+﻿using System.Diagnostics;
+
+namespace TimerBenchmark;
+
+internal sealed class BootConfiguration
+{
+    // bcdedit fails transiently while another process holds the BCD store, so non-zero exits are retried with backoff
+    private const int Attempts = 3;
+    private static readonly TimeSpan InitialBackoff = TimeSpan.FromMilliseconds(250);
+
+    // useplatformclock forces HPET as the QueryPerformanceCounter source
+    public bool UsePlatformClock { get; private init; }
+
+    // disabledynamictick keeps the timer tick running while the system is idle
+    public bool DisableDynamicTick { get; private init; }
+
+    public static async Task<BootConfiguration> QueryAsync()
+    {
+        TimeSpan backoff = InitialBackoff;
+
+        for (int attempt = 1; ; attempt++)
+        {
+            try
+            {
+                return Parse(await EnumerateCurrentAsync());
+            }
+            catch (InvalidOperationException) when (attempt < Attempts)
+            {
+                await Task.Delay(backoff);
+                backoff *= 2;
+            }
+        }
+    }
+
+    private static async Task<string> EnumerateCurrentAsync()
+    {
+        ProcessStartInfo startInfo = new ProcessStartInfo
+        {
+            FileName = "bcdedit",
+            Arguments = "/enum {current}",
+            UseShellExecute = false,
+            RedirectStandardOutput = true,
+            RedirectStandardError = true
+        };
+
+        using Process process = Process.Start(startInfo)
+                                ?? throw new InvalidOperationException("bcdedit failed to start");
+        string output = await process.StandardOutput.ReadToEndAsync();
+        await process.WaitForExitAsync();
+
+        if (process.ExitCode != 0)
+        {
+            throw new InvalidOperationException($"bcdedit exited with code {process.ExitCode}");
+        }
+
+        return output;
+    }
+
+    public static BootConfiguration Parse(string output)
+    {
+        bool usePlatformClock = false;
+        bool disableDynamicTick = false;
+
+        foreach (string line in output.Split('\n', StringSplitOptions.RemoveEmptyEntries | StringSplitOptions.TrimEntries))
+        {
+            string[] parts = line.Split(' ', 2, StringSplitOptions.RemoveEmptyEntries | StringSplitOptions.TrimEntries);
+            if (parts.Length != 2)
+            {
+                continue;
+            }
+
+            bool enabled = parts[1].Equals("Yes", StringComparison.OrdinalIgnoreCase);
+
+            if (parts[0].Equals("useplatformclock", StringComparison.OrdinalIgnoreCase))
+            {
+                usePlatformClock = enabled;
+            }
+            else if (parts[0].Equals("disabledynamictick", StringComparison.OrdinalIgnoreCase))
+            {
+                disableDynamicTick = enabled;
+            }
+        }
+
+        return new BootConfiguration { UsePlatformClock = usePlatformClock, DisableDynamicTick = disableDynamicTick };
+    }
+
+    public void Report()
+    {
+        Console.WriteLine(
+            $"info: HPET as platform clock (bcdedit useplatformclock): {(UsePlatformClock ? "on" : "off")}");
+        Console.WriteLine($"info: Dynamic Tick: {(DisableDynamicTick ? "disabled" : "enabled")}");
+
+        if (UsePlatformClock)
+        {
+            Console.WriteLine("info: HPET is forced as the platform clock, run 'bcdedit /deletevalue useplatformclock' and reboot");
+
+            // Users disable the HPET device and then wonder why this still says "on"
+            Console.WriteLine(
+                "info: this is the boot setting that makes Windows use HPET as its clock, not whether the HPET device exists; disabling the device in Device Manager does not change it");
+        }
+
+        if (!DisableDynamicTick)
+        {
+            Console.WriteLine("info: dynamic tick is enabled, run 'bcdedit /set disabledynamictick yes' and reboot");
+        }
+    }
+}
@@ -0,0 +1,92 @@
+﻿namespace TimerBenchmark;
+
+// Renders delta and STDEV against resolution with the optimal marked
+internal static class ChartRenderer
+{
+    private const int Width = 900;
+    private const int Height = 500;
+    private const int Left = 80;
+    private const int Right = 20;
+    private const int Top = 40;
+    private const int Bottom = 50;
+    private const int Ticks = 5;
+
+    private static readonly (byte, byte, byte) Background = (255, 255, 255);
+    private static readonly (byte, byte, byte) Axis = (60, 60, 60);
+    private static readonly (byte, byte, byte) Grid = (225, 225, 225);
+    private static readonly (byte, byte, byte) DeltaColor = (31, 119, 180);
+    private static readonly (byte, byte, byte) StdevColor = (255, 127, 14);
+    private static readonly (byte, byte, byte) OptimalColor = (214, 39, 40);
+
+    public static async Task RenderAsync(BenchmarkReport report, string path)
+    {
+        PngCanvas canvas = new PngCanvas(Width, Height, Background);
+        IReadOnlyList<BenchmarkResult> results = report.Results;
+
+        double minX = results.Min(r => r.ResolutionMs);
+        double maxX = results.Max(r => r.ResolutionMs);
+        double maxY = results.Max(r => Math.Max(r.DeltaMs, r.Stdev)) * 1.1;
+        if (maxX <= minX)
+        {
+            (minX, maxX) = (minX - 0.0005, maxX + 0.0005);
+        }
+
+        if (maxY <= 0)
+        {
+            maxY = 1;
+        }
+
+        int plotWidth = Width - Left - Right;
+        int plotHeight = Height - Top - Bottom;
+        int ToX(double value) => Left + (int)Math.Round((value - minX) / (maxX - minX) * plotWidth);
+        int ToY(double value) => Top + plotHeight - (int)Math.Round(value / maxY * plotHeight);
+
+        for (int tick = 0; tick <= Ticks; tick++)
+        {
+            double xValue = minX + (maxX - minX) * tick / Ticks;
+            double yValue = maxY * tick / Ticks;
+            int x = ToX(xValue);
+            int y = ToY(yValue);
+
+            canvas.DrawLine(x, Top, x, Top + plotHeight, Grid);
+            canvas.DrawLine(Left, y, Left + plotWidth, y, Grid);
+            canvas.DrawText(x - 18, Top + plotHeight + 8, $"{xValue:F4}", Axis);
+            canvas.DrawText(Left - 48, y - 3, $"{yValue:F3}", Axis);
+        }
+
+        canvas.DrawLine(Left, Top, Left, Top + plotHeight, Axis);
+        canvas.DrawLine(Left, Top + plotHeight, Left + plotWidth, Top + plotHeight, Axis);
+        canvas.DrawText(Left + plotWidth / 2 - 60, Height - 18, "Resolution (ms)", Axis);
+        canvas.DrawText(8, Top - 20, "ms", Axis);
+
+        if (report.Optimal is { } optimal)
+        {
+            int x = ToX(optimal.ResolutionMs);
+            canvas.DrawLine(x, Top, x, Top + plotHeight, OptimalColor);
+            canvas.DrawText(Math.Min(x + 4, Width - 150), Top + 4, $"Optimal {optimal.ResolutionMs}ms", OptimalColor);
+        }
+
+        // Adaptive sweeps, resolution lists and merged files leave rows out of order; lines must follow the x axis
+        List<BenchmarkResult> ordered = results.OrderBy(r => r.ResolutionMs).ToList();
+        for (int i = 1; i < ordered.Count; i++)
+        {
+            canvas.DrawLine(ToX(ordered[i - 1].ResolutionMs), ToY(ordered[i - 1].DeltaMs),
+                ToX(ordered[i].ResolutionMs), ToY(ordered[i].DeltaMs), DeltaColor);
+            canvas.DrawLine(ToX(ordered[i - 1].ResolutionMs), ToY(ordered[i - 1].Stdev),
+                ToX(ordered[i].ResolutionMs), ToY(ordered[i].Stdev), StdevColor);
+        }
+
+        foreach (BenchmarkResult result in results)
+        {
+            canvas.FillRect(ToX(result.ResolutionMs) - 1, ToY(result.DeltaMs) - 1, 3, 3, DeltaColor);
+            canvas.FillRect(ToX(result.ResolutionMs) - 1, ToY(result.Stdev) - 1, 3, 3, StdevColor);
+        }
+
+        canvas.FillRect(Left + 10, 14, 12, 4, DeltaColor);
+        canvas.DrawText(Left + 28, 12, "Delta", Axis);
+        canvas.FillRect(Left + 80, 14, 12, 4, StdevColor);
+        canvas.DrawText(Left + 98, 12, "STDEV", Axis);
+
+        await canvas.SaveAsync(path);
+    }
+}
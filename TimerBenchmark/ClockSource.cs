@@ -0,0 +1,38 @@
+﻿using System.Diagnostics;
+
+namespace TimerBenchmark;
+
+internal static class ClockSource
+{
+    private const long HpetFrequency = 14_318_180;
+    private const long AcpiPmFrequency = 3_579_545;
+    private const long NormalizedTscFrequency = 10_000_000;
+
+    // QueryPerformanceCounter does not report its backing clock, but each source runs at a well-known frequency
+    public static string Detect(long frequency)
+    {
+        return frequency switch
+        {
+            HpetFrequency => "HPET",
+            AcpiPmFrequency => "ACPI PM timer",
+            NormalizedTscFrequency => "TSC",
+            // Builds before Windows 10 1607 expose the TSC divided by 1024 instead of normalizing it to 10 MHz
+            _ => "TSC (inferred)"
+        };
+    }
+
+    public static void Report()
+    {
+        long frequency = Stopwatch.Frequency;
+        string source = Detect(frequency);
+        Explain.Write(
+            $"QueryPerformanceCounter runs at {frequency} Hz; HPET runs at {HpetFrequency} Hz, the ACPI PM timer at {AcpiPmFrequency} Hz and Windows normalizes the TSC to {NormalizedTscFrequency} Hz");
+        Console.WriteLine($"info: QueryPerformanceCounter frequency: {frequency} Hz (source: {source})");
+
+        if (source == "HPET")
+        {
+            Console.WriteLine(
+                "info: measurements are timed with HPET; run 'bcdedit /deletevalue useplatformclock' and reboot to switch to TSC (disabling the HPET device in Device Manager does not change the clock source)");
+        }
+    }
+}
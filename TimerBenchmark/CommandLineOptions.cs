@@ -0,0 +1,790 @@
+﻿using System.Diagnostics;
+using System.Text.Json;
+
+namespace TimerBenchmark;
+
+// Options come from the "Options" section of appsettings.json first and are then overridden on the command line
+internal sealed class CommandLineOptions
+{
+    public int DiscardSamples { get; private set; }
+    public bool StreamJsonLines { get; private set; }
+    public string? StreamJsonLinesPath { get; private set; }
+    public bool JsonToStdout => StreamJsonLines && StreamJsonLinesPath is null;
+    public ErrorPolicy OnError { get; private set; } = ErrorPolicy.Continue;
+    public bool Tui { get; private set; }
+    public string? BaselinePath { get; private set; }
+    public double FailThresholdPercent { get; private set; } = 10;
+    public double? TargetDelta { get; private set; }
+    public string? HistoryPath { get; private set; }
+    public RetryAggregation RetryAggregation { get; private set; } = RetryAggregation.First;
+    public bool Explain { get; private set; }
+    public string? SqlitePath { get; private set; }
+    public bool StandardSet { get; private set; }
+    public bool NoClamp { get; private set; }
+    public IReadOnlyList<double>? SleepDurations { get; private set; }
+    public bool AdaptiveSamples { get; private set; }
+    public double TargetSem { get; private set; } = 0.01;
+    public int MaxSamples { get; private set; } = 1000;
+    public CompareHpetStage CompareHpet { get; private set; } = CompareHpetStage.None;
+    public double? FailOnNoImprovementPercent { get; private set; }
+    public bool PerCoreType { get; private set; }
+    public RoundingMode Rounding { get; private set; } = RoundingMode.Nearest;
+    public string? PlotPath { get; private set; }
+    public TimeSpan? TimePerResolution { get; private set; }
+    public ProcessPriorityClass? Priority { get; private set; }
+    public int? AffinityCore { get; private set; }
+    public bool ListTimerInfo { get; private set; }
+    public string? LoadProcess { get; private set; }
+    public string? LoadCommand { get; private set; }
+    public SortOrder SortBy { get; private set; } = SortOrder.Resolution;
+    public double OptimalBand { get; private set; }
+    public DeltaThresholds DeltaThresholds { get; private set; } = DeltaThresholds.Default;
+    public Command Command { get; private set; } = Command.Benchmark;
+    public double SetResolutionMs { get; private set; }
+    public bool Hold { get; private set; }
+    public bool TraceOptimal { get; private set; }
+    public string? TraceOptimalPath { get; private set; }
+    public double? RefineNoisy { get; private set; }
+    public int? RefineSamples { get; private set; }
+    public bool Ascii { get; private set; }
+    public int WarmupIterations { get; private set; }
+    public int CooldownMs { get; private set; }
+    public bool NativeSet { get; private set; }
+    public bool DumpConfig { get; private set; }
+    public bool HighResTimer { get; private set; }
+    public string? SummaryImagePath { get; private set; }
+    public double? NoiseCheckStdev { get; private set; }
+    public string? DependencyDir { get; private set; }
+    public bool MeasureTickJitter { get; private set; }
+    public bool UnitsColumn { get; private set; }
+    public string? OutputPath { get; private set; }
+    public string ResultsPath => OutputPath ?? "results.txt";
+    public string JsonResultsPath => Path.ChangeExtension(ResultsPath, ".json");
+    public bool DryMeasure { get; private set; }
+    public int Precision { get; private set; } = NumberFormat.DefaultDigits;
+    public bool HoldInThread { get; private set; }
+    public string? RawSamplesPath { get; private set; }
+    public double? RawSamplesResolutionMs { get; private set; }
+    public IReadOnlyList<string> MergePaths { get; private set; } = Array.Empty<string>();
+    public bool JsonSummary { get; private set; }
+    public bool Timing { get; private set; }
+    public TimeSpan? GlobalTimeout { get; private set; }
+    public TimeSpan? Watch { get; private set; }
+    public bool WatchClear { get; private set; }
+    public bool NoSave { get; private set; }
+    public bool Timestamps { get; private set; }
+    public double? StartValue { get; private set; }
+    public double? IncrementValue { get; private set; }
+    public double? EndValue { get; private set; }
+    public int? SampleValue { get; private set; }
+    public bool Headless { get; private set; }
+    public ResultsFormat Format { get; private set; } = ResultsFormat.Csv;
+    public OptimalMetric OptimalBy { get; private set; } = OptimalMetric.Mean;
+    public double? TrimPercent { get; private set; }
+    public int Retries { get; private set; } = 2;
+    public int Passes { get; private set; } = 1;
+    public double? AdaptiveIncrement { get; private set; }
+
+    public const string DefaultConfigPath = "appsettings.json";
+
+    // Read when --config is not given, e.g. to point a shortcut at a configuration kept elsewhere
+    public const string ConfigPathVariable = "TIMERRES_CONFIG";
+
+    // The configuration supplies the defaults for every other option, so --config is picked out before it is loaded
+    public static string ConfigPathFrom(string[] args)
+    {
+        if (ExplicitConfigPath(args) is { } explicitPath)
+        {
+            return Path.GetFullPath(explicitPath);
+        }
+
+        // Started from Explorer or a shortcut the working directory is often elsewhere, so fall back to the exe's folder
+        string besideExe = Path.Combine(AppContext.BaseDirectory, DefaultConfigPath);
+        return !File.Exists(DefaultConfigPath) && File.Exists(besideExe) ? besideExe : Path.GetFullPath(DefaultConfigPath);
+    }
+
+    // The path named by --config or TIMERRES_CONFIG, which must exist unless the wizard is about to create it
+    public static string? ExplicitConfigPath(string[] args)
+    {
+        int index = Array.IndexOf(args, "--config");
+        if (index >= 0 && index + 1 < args.Length)
+        {
+            return args[index + 1];
+        }
+
+        return Environment.GetEnvironmentVariable(ConfigPathVariable) is { Length: > 0 } fromEnvironment
+            ? fromEnvironment
+            : null;
+    }
+
+    public static CommandLineOptions Parse(string[] args, CommandLineOptions? defaults = null)
+    {
+        CommandLineOptions options = defaults ?? new CommandLineOptions();
+        int first = 0;
+
+        if (args.Length > 0 && args[0] == "set")
+        {
+            options.Command = Command.Set;
+            options.SetResolutionMs = ParsePositiveDouble(args, ref first);
+            first++;
+        }
+        else if (args.Length > 0 && args[0] == "doctor")
+        {
+            options.Command = Command.Doctor;
+            first = 1;
+        }
+        else if (args.Length > 0 && args[0] == "merge")
+        {
+            options.Command = Command.Merge;
+            options.MergePaths = args.Skip(1).TakeWhile(arg => !arg.StartsWith("--")).ToList();
+            first = options.MergePaths.Count + 1;
+
+            if (options.MergePaths.Count < 2)
+            {
+                throw new ArgumentException("'merge' expects at least two results files");
+            }
+        }
+
+        for (int i = first; i < args.Length; i++)
+        {
+            switch (args[i])
+            {
+                case "--discard-samples":
+                    options.DiscardSamples = ParseNonNegativeInt(args, ref i);
+                    break;
+                case "--stream-jsonl":
+                    options.StreamJsonLines = true;
+                    options.StreamJsonLinesPath = OptionalValue(args, ref i);
+                    break;
+                case "--on-error":
+                    options.OnError = NextValue(args, ref i) switch
+                    {
+                        "continue" => ErrorPolicy.Continue,
+                        "abort" => ErrorPolicy.Abort,
+                        var value => throw new ArgumentException(
+                            $"'--on-error' expects 'continue' or 'abort', got '{value}'")
+                    };
+                    break;
+                case "--tui":
+                    options.Tui = true;
+                    break;
+                case "--baseline":
+                    options.BaselinePath = NextValue(args, ref i);
+                    break;
+                case "--fail-threshold":
+                    options.FailThresholdPercent = ParsePercent(args, ref i);
+                    break;
+                case "--target-delta":
+                    options.TargetDelta = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--history":
+                    options.HistoryPath = NextValue(args, ref i);
+                    break;
+                case "--retry-aggregation":
+                    options.RetryAggregation = NextValue(args, ref i) switch
+                    {
+                        "first" => RetryAggregation.First,
+                        "best" => RetryAggregation.Best,
+                        "median" => RetryAggregation.Median,
+                        var value => throw new ArgumentException(
+                            $"'--retry-aggregation' expects 'best', 'median' or 'first', got '{value}'")
+                    };
+                    break;
+                case "--explain":
+                    options.Explain = true;
+                    break;
+                case "--sqlite":
+                    options.SqlitePath = NextValue(args, ref i);
+                    break;
+                case "--standard-set":
+                    options.StandardSet = true;
+                    break;
+                case "--clamp":
+                    // Clamping is the default now; still accepted so existing scripts keep working
+                    break;
+                case "--no-clamp":
+                    options.NoClamp = true;
+                    break;
+                case "--sleep-durations":
+                    options.SleepDurations = ParsePositiveDoubleList(args, ref i);
+                    break;
+                case "--adaptive-samples":
+                    options.AdaptiveSamples = true;
+                    break;
+                case "--target-sem":
+                    options.TargetSem = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--max-samples":
+                    options.MaxSamples = ParsePositiveInt(args, ref i);
+                    break;
+                case "--compare-hpet":
+                    options.CompareHpet = OptionalValue(args, ref i) switch
+                    {
+                        null => CompareHpetStage.Start,
+                        "continue" => CompareHpetStage.Continue,
+                        var value => throw new ArgumentException(
+                            $"'--compare-hpet' expects no value or 'continue', got '{value}'")
+                    };
+                    break;
+                case "--fail-on-no-improvement":
+                    options.FailOnNoImprovementPercent = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--per-core-type":
+                    options.PerCoreType = true;
+                    break;
+                case "--rounding":
+                    options.Rounding = NextValue(args, ref i) switch
+                    {
+                        "nearest" => RoundingMode.Nearest,
+                        "floor" => RoundingMode.Floor,
+                        "ceil" => RoundingMode.Ceil,
+                        var value => throw new ArgumentException(
+                            $"'--rounding' expects 'nearest', 'floor' or 'ceil', got '{value}'")
+                    };
+                    break;
+                case "--plot":
+                    options.PlotPath = NextValue(args, ref i);
+                    break;
+                case "--time-per-resolution":
+                    options.TimePerResolution = ParseDuration(args, ref i);
+                    break;
+                case "--priority":
+                    string priority = NextValue(args, ref i);
+                    options.Priority = Enum.TryParse(priority, ignoreCase: true, out ProcessPriorityClass parsedPriority)
+                        ? parsedPriority
+                        : throw new ArgumentException(
+                            $"'--priority' expects Idle, BelowNormal, Normal, AboveNormal, High or RealTime, got '{priority}'");
+                    break;
+                case "--affinity":
+                    options.AffinityCore = ParseNonNegativeInt(args, ref i);
+                    break;
+                case "--list-timer-info":
+                    options.ListTimerInfo = true;
+                    break;
+                case "--load-process":
+                    options.LoadProcess = NextValue(args, ref i);
+                    break;
+                case "--load-command":
+                    options.LoadCommand = NextValue(args, ref i);
+                    break;
+                case "--sort-by":
+                    options.SortBy = NextValue(args, ref i) switch
+                    {
+                        "resolution" => SortOrder.Resolution,
+                        "delta" => SortOrder.Delta,
+                        "stdev" => SortOrder.Stdev,
+                        var value => throw new ArgumentException(
+                            $"'--sort-by' expects 'delta', 'stdev' or 'resolution', got '{value}'")
+                    };
+                    break;
+                case "--optimal-band":
+                    options.OptimalBand = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--delta-thresholds":
+                {
+                    List<double> thresholds = ParsePositiveDoubleList(args, ref i);
+                    if (thresholds.Count != 2 || thresholds[0] >= thresholds[1])
+                    {
+                        throw new ArgumentException(
+                            $"'--delta-thresholds' expects two increasing values such as 1,2, got '{args[i]}'");
+                    }
+
+                    options.DeltaThresholds = new DeltaThresholds(thresholds[0], thresholds[1]);
+                    break;
+                }
+                case "--hold":
+                    options.Hold = true;
+                    break;
+                case "--trace-optimal":
+                    options.TraceOptimal = true;
+                    options.TraceOptimalPath = OptionalValue(args, ref i);
+                    break;
+                case "--refine-noisy":
+                    options.RefineNoisy = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--refine-samples":
+                    options.RefineSamples = ParsePositiveInt(args, ref i);
+                    break;
+                case "--ascii":
+                    options.Ascii = true;
+                    break;
+                case "--warmup-iterations":
+                    options.WarmupIterations = ParseNonNegativeInt(args, ref i);
+                    break;
+                case "--cooldown-ms":
+                    options.CooldownMs = ParseNonNegativeInt(args, ref i);
+                    break;
+                case "--native-set":
+                    options.NativeSet = true;
+                    break;
+                case "--dump-config":
+                    options.DumpConfig = true;
+                    break;
+                case "--high-res-timer":
+                    options.HighResTimer = true;
+                    break;
+                case "--summary-image":
+                    options.SummaryImagePath = NextValue(args, ref i);
+                    break;
+                case "--noise-check":
+                    options.NoiseCheckStdev = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--dependency-dir":
+                    options.DependencyDir = NextValue(args, ref i);
+                    break;
+                case "--measure-tick-jitter":
+                    options.MeasureTickJitter = true;
+                    break;
+                case "--units-column":
+                    options.UnitsColumn = true;
+                    break;
+                case "--output":
+                    options.OutputPath = NextValue(args, ref i);
+                    break;
+                case "--from-raw-samples":
+                    options.RawSamplesPath = NextValue(args, ref i);
+                    break;
+                case "--resolution":
+                    options.RawSamplesResolutionMs = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--dry-measure":
+                    options.DryMeasure = true;
+                    break;
+                case "--precision":
+                    options.Precision = ParseNonNegativeInt(args, ref i);
+                    break;
+                case "--hold-in-thread":
+                    options.HoldInThread = true;
+                    break;
+                case "--json-summary":
+                    options.JsonSummary = true;
+                    break;
+                case "--timing":
+                    options.Timing = true;
+                    break;
+                case "--global-timeout":
+                    options.GlobalTimeout = ParseDuration(args, ref i);
+                    break;
+                case "--watch":
+                    options.Watch = ParseDuration(args, ref i);
+                    break;
+                case "--watch-clear":
+                    options.WatchClear = true;
+                    break;
+                case "--no-save":
+                    options.NoSave = true;
+                    break;
+                case "--timestamps":
+                    options.Timestamps = true;
+                    break;
+                case "--start":
+                    options.StartValue = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--increment":
+                    options.IncrementValue = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--end":
+                    options.EndValue = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--samples":
+                    options.SampleValue = ParsePositiveInt(args, ref i);
+                    break;
+                case "--headless":
+                    options.Headless = true;
+                    break;
+                case "--format":
+                    options.Format = NextValue(args, ref i) switch
+                    {
+                        "csv" => ResultsFormat.Csv,
+                        "json" => ResultsFormat.Json,
+                        var value => throw new ArgumentException($"'--format' expects 'csv' or 'json', got '{value}'")
+                    };
+                    break;
+                case "--optimal-by":
+                    options.OptimalBy = NextValue(args, ref i) switch
+                    {
+                        "mean" => OptimalMetric.Mean,
+                        "median" => OptimalMetric.Median,
+                        var value => throw new ArgumentException($"'--optimal-by' expects 'mean' or 'median', got '{value}'")
+                    };
+                    break;
+                case "--trim-percent":
+                    options.TrimPercent = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--retries":
+                    options.Retries = ParseNonNegativeInt(args, ref i);
+                    break;
+                case "--passes":
+                    options.Passes = ParsePositiveInt(args, ref i);
+                    break;
+                case "--adaptive":
+                    options.AdaptiveIncrement = ParsePositiveDouble(args, ref i);
+                    break;
+                case "--config":
+                    // Already consumed by ConfigPathFrom
+                    NextValue(args, ref i);
+                    break;
+                default:
+                    throw new ArgumentException($"unrecognized argument '{args[i]}'");
+            }
+        }
+
+        options.Validate();
+        return options;
+    }
+
+    // Property names match the "Options" section so the output can be pasted back into the configuration
+    public void WriteJson(Utf8JsonWriter json)
+    {
+        json.WriteNumber("DiscardSamples", DiscardSamples);
+        json.WriteBoolean("StreamJsonLines", StreamJsonLines);
+        json.WriteString("StreamJsonLinesPath", StreamJsonLinesPath);
+        json.WriteString("OnError", OnError.ToString());
+        json.WriteBoolean("Tui", Tui);
+        json.WriteString("BaselinePath", BaselinePath);
+        json.WriteNumber("FailThresholdPercent", FailThresholdPercent);
+        WriteNumberOrNull(json, "TargetDelta", TargetDelta);
+        json.WriteString("HistoryPath", HistoryPath);
+        json.WriteString("RetryAggregation", RetryAggregation.ToString());
+        json.WriteBoolean("Explain", Explain);
+        json.WriteString("SqlitePath", SqlitePath);
+        json.WriteBoolean("StandardSet", StandardSet);
+        json.WriteBoolean("NoClamp", NoClamp);
+
+        if (SleepDurations is null)
+        {
+            json.WriteNull("SleepDurations");
+        }
+        else
+        {
+            json.WriteStartArray("SleepDurations");
+            foreach (double value in SleepDurations)
+            {
+                json.WriteNumberValue(value);
+            }
+
+            json.WriteEndArray();
+        }
+
+        json.WriteBoolean("AdaptiveSamples", AdaptiveSamples);
+        json.WriteNumber("TargetSem", TargetSem);
+        json.WriteNumber("MaxSamples", MaxSamples);
+        json.WriteString("CompareHpet", CompareHpet.ToString());
+        WriteNumberOrNull(json, "FailOnNoImprovementPercent", FailOnNoImprovementPercent);
+        json.WriteBoolean("PerCoreType", PerCoreType);
+        json.WriteString("Rounding", Rounding.ToString());
+        json.WriteString("PlotPath", PlotPath);
+        json.WriteString("TimePerResolution", TimePerResolution?.ToString());
+        json.WriteString("Priority", Priority?.ToString());
+        WriteNumberOrNull(json, "AffinityCore", AffinityCore);
+        json.WriteBoolean("ListTimerInfo", ListTimerInfo);
+        json.WriteString("LoadProcess", LoadProcess);
+        json.WriteString("LoadCommand", LoadCommand);
+        json.WriteString("SortBy", SortBy.ToString());
+        json.WriteNumber("OptimalBand", OptimalBand);
+        // Written in the shape the configuration binder reads back, unlike the "good,fair" form of the flag
+        json.WriteStartObject("DeltaThresholds");
+        json.WriteNumber("Good", DeltaThresholds.Good);
+        json.WriteNumber("Fair", DeltaThresholds.Fair);
+        json.WriteEndObject();
+        json.WriteString("Command", Command.ToString());
+        json.WriteNumber("SetResolutionMs", SetResolutionMs);
+        json.WriteBoolean("Hold", Hold);
+        json.WriteBoolean("TraceOptimal", TraceOptimal);
+        json.WriteString("TraceOptimalPath", TraceOptimalPath);
+        WriteNumberOrNull(json, "RefineNoisy", RefineNoisy);
+        WriteNumberOrNull(json, "RefineSamples", RefineSamples);
+        json.WriteBoolean("Ascii", Ascii);
+        json.WriteNumber("WarmupIterations", WarmupIterations);
+        json.WriteNumber("CooldownMs", CooldownMs);
+        json.WriteBoolean("NativeSet", NativeSet);
+        json.WriteBoolean("HighResTimer", HighResTimer);
+        json.WriteString("SummaryImagePath", SummaryImagePath);
+        WriteNumberOrNull(json, "NoiseCheckStdev", NoiseCheckStdev);
+        json.WriteString("DependencyDir", DependencyDir);
+        json.WriteBoolean("MeasureTickJitter", MeasureTickJitter);
+        json.WriteBoolean("UnitsColumn", UnitsColumn);
+        json.WriteString("OutputPath", OutputPath);
+        json.WriteBoolean("DryMeasure", DryMeasure);
+        json.WriteNumber("Precision", Precision);
+        json.WriteBoolean("HoldInThread", HoldInThread);
+        json.WriteString("RawSamplesPath", RawSamplesPath);
+        WriteNumberOrNull(json, "RawSamplesResolutionMs", RawSamplesResolutionMs);
+        json.WriteBoolean("JsonSummary", JsonSummary);
+        json.WriteBoolean("Timing", Timing);
+        json.WriteString("GlobalTimeout", GlobalTimeout?.ToString());
+        json.WriteString("Watch", Watch?.ToString());
+        json.WriteBoolean("WatchClear", WatchClear);
+        json.WriteBoolean("NoSave", NoSave);
+        json.WriteBoolean("Timestamps", Timestamps);
+        WriteNumberOrNull(json, "StartValue", StartValue);
+        WriteNumberOrNull(json, "IncrementValue", IncrementValue);
+        WriteNumberOrNull(json, "EndValue", EndValue);
+        WriteNumberOrNull(json, "SampleValue", SampleValue);
+        json.WriteBoolean("Headless", Headless);
+        json.WriteString("Format", Format.ToString());
+        json.WriteString("OptimalBy", OptimalBy.ToString());
+        WriteNumberOrNull(json, "TrimPercent", TrimPercent);
+        json.WriteNumber("Retries", Retries);
+        json.WriteNumber("Passes", Passes);
+        WriteNumberOrNull(json, "AdaptiveIncrement", AdaptiveIncrement);
+    }
+
+    private static void WriteNumberOrNull(Utf8JsonWriter json, string name, double? value)
+    {
+        if (value is { } number)
+        {
+            json.WriteNumber(name, number);
+        }
+        else
+        {
+            json.WriteNull(name);
+        }
+    }
+
+    // Values bound from appsettings.json bypass the per-flag parsing, so re-check them here
+    private void Validate()
+    {
+        if (DiscardSamples < 0 || Retries < 0 || Passes <= 0 || WarmupIterations < 0 || CooldownMs < 0 || FailThresholdPercent < 0 || OptimalBand < 0 || TargetDelta <= 0 || NoiseCheckStdev <= 0 || TargetSem <= 0 || MaxSamples <= 0)
+        {
+            throw new ArgumentException("options must not contain negative or zero limits");
+        }
+
+        if (SleepDurations is not null && (SleepDurations.Count == 0 || SleepDurations.Any(duration => duration <= 0)))
+        {
+            throw new ArgumentException("SleepDurations must contain at least one positive value");
+        }
+
+        if (RawSamplesPath is not null && RawSamplesResolutionMs is null)
+        {
+            throw new ArgumentException("'--from-raw-samples' needs '--resolution <ms>' for the resolution the samples were taken at");
+        }
+
+        if (FailOnNoImprovementPercent is not null && CompareHpet != CompareHpetStage.Continue)
+        {
+            throw new ArgumentException("'--fail-on-no-improvement' only applies to '--compare-hpet continue'");
+        }
+
+        if (NoSave)
+        {
+            // Everything that writes a file, or reads back the results a previous run saved
+            (bool Set, string Flag)[] fileOptions =
+            {
+                (OutputPath is not null, "--output"),
+                (HistoryPath is not null, "--history"),
+                (BaselinePath is not null, "--baseline"),
+                (Watch is not null, "--watch"),
+                (SqlitePath is not null, "--sqlite"),
+                (PlotPath is not null, "--plot"),
+                (SummaryImagePath is not null, "--summary-image"),
+                (TraceOptimalPath is not null, "--trace-optimal <path>"),
+                (StreamJsonLinesPath is not null, "--stream-jsonl <path>"),
+                (RefineNoisy is not null, "--refine-noisy"),
+                (CompareHpet != CompareHpetStage.None, "--compare-hpet"),
+                (RawSamplesPath is not null, "--from-raw-samples"),
+                (Command == Command.Merge, "merge")
+            };
+            string[] conflicting = fileOptions.Where(option => option.Set).Select(option => option.Flag).ToArray();
+
+            if (conflicting.Length > 0)
+            {
+                throw new ArgumentException(
+                    $"'--no-save' writes nothing to disk and cannot be combined with {string.Join(", ", conflicting)}");
+            }
+        }
+
+        if (AdaptiveIncrement <= 0)
+        {
+            throw new ArgumentException($"AdaptiveIncrement must be positive, got {AdaptiveIncrement}");
+        }
+
+        // Later steps of the adaptive search depend on the results of earlier ones, which fixed lists cannot follow
+        if (AdaptiveIncrement is not null && (StandardSet || RefineNoisy is not null || Passes > 1))
+        {
+            throw new ArgumentException("'--adaptive' cannot be combined with '--standard-set', '--refine-noisy' or '--passes'");
+        }
+
+        // Trimming half the samples from each end would leave nothing
+        if (TrimPercent is <= 0 or >= 50)
+        {
+            throw new ArgumentException($"TrimPercent must be greater than 0 and less than 50, got {TrimPercent}");
+        }
+
+        // Math.Round accepts at most 15 decimal places
+        if (Precision is < 0 or > 15)
+        {
+            throw new ArgumentException($"Precision must be between 0 and 15, got {Precision}");
+        }
+
+        if (TimePerResolution <= TimeSpan.Zero)
+        {
+            throw new ArgumentException("TimePerResolution must be positive");
+        }
+
+        if (AffinityCore is { } core && (core < 0 || core >= Environment.ProcessorCount))
+        {
+            throw new ArgumentException(
+                $"affinity core {core} does not exist, this system has {Environment.ProcessorCount} logical processors");
+        }
+
+        // ProcessorAffinity is a 64-bit mask over the current processor group only
+        if (AffinityCore >= 64)
+        {
+            throw new ArgumentException(
+                $"affinity core {AffinityCore} is outside the first processor group, only cores 0-63 can be pinned");
+        }
+    }
+
+    private static string NextValue(string[] args, ref int index)
+    {
+        if (index + 1 >= args.Length)
+        {
+            throw new ArgumentException($"missing value for '{args[index]}'");
+        }
+
+        return args[++index];
+    }
+
+    private static string? OptionalValue(string[] args, ref int index)
+    {
+        if (index + 1 >= args.Length || args[index + 1].StartsWith("--"))
+        {
+            return null;
+        }
+
+        return args[++index];
+    }
+
+    private static double ParsePositiveDouble(string[] args, ref int index)
+    {
+        string name = args[index];
+        string value = NextValue(args, ref index);
+
+        if (!double.TryParse(value, out double parsed) || parsed <= 0)
+        {
+            throw new ArgumentException($"'{name}' expects a positive number, got '{value}'");
+        }
+
+        return parsed;
+    }
+
+    private static List<double> ParsePositiveDoubleList(string[] args, ref int index)
+    {
+        string name = args[index];
+        string value = NextValue(args, ref index);
+        List<double> values = new List<double>();
+
+        foreach (string item in value.Split(',', StringSplitOptions.RemoveEmptyEntries | StringSplitOptions.TrimEntries))
+        {
+            if (!double.TryParse(item, out double parsed) || parsed <= 0)
+            {
+                throw new ArgumentException($"'{name}' expects a comma-separated list of positive numbers, got '{value}'");
+            }
+
+            values.Add(parsed);
+        }
+
+        if (values.Count == 0)
+        {
+            throw new ArgumentException($"'{name}' expects at least one value");
+        }
+
+        return values;
+    }
+
+    // Accepts durations such as "500ms", "2s", "10m" or "1h"
+    private static TimeSpan ParseDuration(string[] args, ref int index)
+    {
+        string name = args[index];
+        string value = NextValue(args, ref index);
+
+        (string number, double unitMs) = value switch
+        {
+            _ when value.EndsWith("ms") => (value[..^2], 1d),
+            _ when value.EndsWith('s') => (value[..^1], 1000d),
+            _ when value.EndsWith('m') => (value[..^1], 60_000d),
+            _ when value.EndsWith('h') => (value[..^1], 3_600_000d),
+            _ => (value, 1000d)
+        };
+
+        if (!double.TryParse(number, out double parsed) || parsed <= 0)
+        {
+            throw new ArgumentException($"'{name}' expects a positive duration such as 500ms, 2s or 10m, got '{value}'");
+        }
+
+        return TimeSpan.FromMilliseconds(parsed * unitMs);
+    }
+
+    private static double ParsePercent(string[] args, ref int index)
+    {
+        string name = args[index];
+        string value = NextValue(args, ref index);
+
+        if (!double.TryParse(value.TrimEnd('%'), out double parsed) || parsed < 0)
+        {
+            throw new ArgumentException($"'{name}' expects a non-negative percentage, got '{value}'");
+        }
+
+        return parsed;
+    }
+
+    private static int ParsePositiveInt(string[] args, ref int index)
+    {
+        string name = args[index];
+        string value = NextValue(args, ref index);
+
+        if (!int.TryParse(value, out int parsed) || parsed <= 0)
+        {
+            throw new ArgumentException($"'{name}' expects a positive integer, got '{value}'");
+        }
+
+        return parsed;
+    }
+
+    private static int ParseNonNegativeInt(string[] args, ref int index)
+    {
+        string name = args[index];
+        string value = NextValue(args, ref index);
+
+        if (!int.TryParse(value, out int parsed) || parsed < 0)
+        {
+            throw new ArgumentException($"'{name}' expects a non-negative integer, got '{value}'");
+        }
+
+        return parsed;
+    }
+}
+
+internal enum ErrorPolicy
+{
+    Continue,
+    Abort
+}
+
+internal enum ResultsFormat
+{
+    Csv,
+    Json
+}
+
+internal enum CompareHpetStage
+{
+    None,
+    Start,
+    Continue
+}
+
+internal enum RoundingMode
+{
+    Nearest,
+    Floor,
+    Ceil
+}
+
+internal enum Command
+{
+    Benchmark,
+    Set,
+    Merge,
+    Doctor
+}
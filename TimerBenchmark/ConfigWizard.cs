@@ -0,0 +1,123 @@
+﻿using System.Text.Json;
+using System.Text.Json.Nodes;
+
+namespace TimerBenchmark;
+
+// Guided setup for first runs: picks a sweep and a sample count that fits the time the user has
+internal static class ConfigWizard
+{
+    // MeasureSleep takes about 2ms per Sleep(1) sample, the same assumption as the time estimate
+    private const double MsPerSample = 2;
+    private const int MinSamples = 20;
+    private const int MaxSamples = 1000;
+
+    // The same leniency as the configuration provider that reads the file
+    private static readonly JsonDocumentOptions ConfigDocumentOptions = new JsonDocumentOptions
+    {
+        CommentHandling = JsonCommentHandling.Skip,
+        AllowTrailingCommas = true
+    };
+
+    public static async Task RunAsync(string configPath)
+    {
+        // Writing back a YAML file would mean a YAML serializer just for the wizard
+        if (Path.GetExtension(configPath).ToLowerInvariant() is ".yaml" or ".yml")
+        {
+            await Console.Error.WriteLineAsync(
+                $"error: the wizard only writes JSON configurations, run it with --config appsettings.json and copy the BenchmarkingParameters into {configPath}");
+            Environment.ExitCode = 1;
+            return;
+        }
+
+        // Read up front, so a file the wizard cannot update is reported before any questions are asked
+        JsonObject root;
+        try
+        {
+            root = File.Exists(configPath)
+                ? JsonNode.Parse(await File.ReadAllTextAsync(configPath), documentOptions: ConfigDocumentOptions) as JsonObject
+                  ?? new JsonObject()
+                : new JsonObject();
+        }
+        catch (JsonException e)
+        {
+            await Console.Error.WriteLineAsync($"error: {configPath} is not valid JSON: {e.Message}");
+            Environment.ExitCode = 1;
+            return;
+        }
+
+        Console.WriteLine($"This wizard writes the BenchmarkingParameters section of {Path.GetFullPath(configPath)}.");
+        Console.WriteLine();
+
+        bool fine = Ask("Run a (c)oarse sweep to find the region of the optimal, or a (f)ine sweep around a known value?",
+            "c", "f") == "f";
+
+        double start;
+        double increment;
+        double end;
+
+        if (fine)
+        {
+            start = AskNumber("Lowest resolution to test in ms", 0.5);
+            end = start + 0.1;
+            increment = 0.002;
+            Console.WriteLine(
+                $"info: a fine sweep covers {start}ms to {end}ms in {increment}ms steps, small enough to separate neighbouring resolutions");
+        }
+        else
+        {
+            (start, increment, end) = (0.5, 0.01, 1.0);
+            Console.WriteLine(
+                $"info: a coarse sweep covers {start}ms to {end}ms in {increment}ms steps, run a fine sweep around the best result afterwards");
+        }
+
+        int steps = (int)Math.Round((end - start) / increment) + 1;
+        double minutes = AskNumber("How many minutes can the benchmark run", 10);
+        int samples = Math.Clamp((int)(minutes * 60000 / (steps * MsPerSample)), MinSamples, MaxSamples);
+        Console.WriteLine(
+            $"info: {steps} resolutions in {minutes} minutes leaves {samples} samples each, more samples give a steadier delta");
+
+        root["BenchmarkingParameters"] = new JsonObject
+        {
+            ["StartValue"] = Math.Round(start, 4),
+            ["IncrementValue"] = increment,
+            ["EndValue"] = Math.Round(end, 4),
+            ["SampleValue"] = samples
+        };
+
+        await File.WriteAllTextAsync(configPath, root.ToJsonString(new JsonSerializerOptions { WriteIndented = true }));
+        Console.WriteLine($"info: configuration saved in {configPath}, run TimerBenchmark.exe as an administrator to start");
+    }
+
+    private static string Ask(string question, params string[] answers)
+    {
+        while (true)
+        {
+            Console.Write($"{question} ({string.Join('/', answers)}) ");
+            string? answer = Console.ReadLine()?.Trim().ToLowerInvariant();
+
+            if (answer is not null && answers.Contains(answer))
+            {
+                return answer;
+            }
+        }
+    }
+
+    private static double AskNumber(string question, double defaultValue)
+    {
+        while (true)
+        {
+            Console.Write($"{question} [{defaultValue}]: ");
+            string? answer = Console.ReadLine()?.Trim();
+
+            if (string.IsNullOrEmpty(answer))
+            {
+                return defaultValue;
+            }
+
+            if (double.TryParse(answer, out double value) && value > 0)
+            {
+                return value;
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+﻿using System.Runtime.InteropServices;
+
+namespace TimerBenchmark;
+
+// conhost starts on the legacy OEM code page, which turns the status symbols and box characters into garbage
+internal static class ConsoleCodePage
+{
+    private const uint Utf8 = 65001;
+
+    // Switches the console to UTF-8 for the lifetime of the process and puts the original code page back on exit
+    public static void UseUtf8()
+    {
+        uint original = GetConsoleOutputCP();
+        if (original == 0 || original == Utf8 || !SetConsoleOutputCP(Utf8))
+        {
+            return;
+        }
+
+        AppDomain.CurrentDomain.ProcessExit += (_, _) => SetConsoleOutputCP(original);
+    }
+
+    [DllImport("kernel32.dll")]
+    private static extern uint GetConsoleOutputCP();
+
+    [DllImport("kernel32.dll", SetLastError = true)]
+    private static extern bool SetConsoleOutputCP(uint codePageId);
+}
@@ -0,0 +1,63 @@
+﻿using System.ComponentModel;
+using System.Runtime.InteropServices;
+
+namespace TimerBenchmark;
+
+internal static class CoreTopology
+{
+    private const int RelationProcessorCore = 0;
+    private const int ErrorInsufficientBuffer = 122;
+
+    // Offsets into SYSTEM_LOGICAL_PROCESSOR_INFORMATION_EX for a PROCESSOR_RELATIONSHIP entry
+    private const int SizeOffset = 4;
+    private const int EfficiencyClassOffset = 9;
+    private const int GroupMaskOffset = 32;
+    private const int GroupOffset = 40;
+
+    // Maps each efficiency class to a processor group 0 affinity mask; hybrid CPUs rank P-cores above E-cores
+    public static SortedDictionary<byte, ulong> EfficiencyClassMasks()
+    {
+        uint length = 0;
+        GetLogicalProcessorInformationEx(RelationProcessorCore, IntPtr.Zero, ref length);
+        if (Marshal.GetLastWin32Error() != ErrorInsufficientBuffer)
+        {
+            throw new Win32Exception(Marshal.GetLastWin32Error());
+        }
+
+        IntPtr buffer = Marshal.AllocHGlobal((int)length);
+        try
+        {
+            if (!GetLogicalProcessorInformationEx(RelationProcessorCore, buffer, ref length))
+            {
+                throw new Win32Exception(Marshal.GetLastWin32Error());
+            }
+
+            SortedDictionary<byte, ulong> masks = new SortedDictionary<byte, ulong>();
+
+            for (int offset = 0; offset < length;)
+            {
+                IntPtr entry = buffer + offset;
+                byte efficiencyClass = Marshal.ReadByte(entry, EfficiencyClassOffset);
+                ulong mask = (ulong)Marshal.ReadInt64(entry, GroupMaskOffset);
+
+                // Only processor group 0 can be targeted with SetThreadAffinityMask
+                if (Marshal.ReadInt16(entry, GroupOffset) == 0)
+                {
+                    masks[efficiencyClass] = masks.GetValueOrDefault(efficiencyClass) | mask;
+                }
+
+                offset += Marshal.ReadInt32(entry, SizeOffset);
+            }
+
+            return masks;
+        }
+        finally
+        {
+            Marshal.FreeHGlobal(buffer);
+        }
+    }
+
+    [DllImport("kernel32.dll", SetLastError = true)]
+    private static extern bool GetLogicalProcessorInformationEx(int relationshipType, IntPtr buffer,
+        ref uint returnedLength);
+}
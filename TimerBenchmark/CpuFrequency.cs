@@ -0,0 +1,50 @@
+﻿using System.Runtime.InteropServices;
+
+namespace TimerBenchmark;
+
+// Averaged PROCESSOR_POWER_INFORMATION across all logical processors
+internal readonly record struct CpuFrequency(uint CurrentMhz, uint MaxMhz, uint LimitMhz, uint MaxIdleState)
+{
+    private const int ProcessorInformation = 11;
+
+    // Below this share of the rated frequency the CPU is treated as downclocked
+    private const double DownclockRatio = 0.8;
+
+    public bool IsDownclocked => MaxMhz > 0 && CurrentMhz < MaxMhz * DownclockRatio;
+
+    // The frequency limit drops below the rated frequency when thermal or power limits kick in
+    public bool IsThrottled => MaxMhz > 0 && LimitMhz > 0 && LimitMhz < MaxMhz;
+
+    public static CpuFrequency? Sample()
+    {
+        int count = Environment.ProcessorCount;
+        ProcessorPowerInformation[] info = new ProcessorPowerInformation[count];
+        int size = Marshal.SizeOf<ProcessorPowerInformation>() * count;
+
+        if (CallNtPowerInformation(ProcessorInformation, IntPtr.Zero, 0, info, size) != 0)
+        {
+            return null;
+        }
+
+        return new CpuFrequency(
+            (uint)info.Average(p => p.CurrentMhz),
+            (uint)info.Average(p => p.MaxMhz),
+            (uint)info.Average(p => p.MhzLimit),
+            info.Max(p => p.MaxIdleState));
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    private struct ProcessorPowerInformation
+    {
+        public uint Number;
+        public uint MaxMhz;
+        public uint CurrentMhz;
+        public uint MhzLimit;
+        public uint MaxIdleState;
+        public uint CurrentIdleState;
+    }
+
+    [DllImport("powrprof.dll")]
+    private static extern uint CallNtPowerInformation(int informationLevel, IntPtr inputBuffer, int inputBufferLength,
+        [Out] ProcessorPowerInformation[] outputBuffer, int outputBufferLength);
+}
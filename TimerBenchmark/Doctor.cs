@@ -0,0 +1,196 @@
+﻿using System.ComponentModel;
+using System.Diagnostics;
+
+namespace TimerBenchmark;
+
+// Checks everything a good run depends on and prints one pass/warn/fail line per check, so a bug report can include
+// the whole environment in one paste
+internal static class Doctor
+{
+    // The oldest helper release whose MeasureSleep accepts --samples and prints the output Measurement.Parse expects
+    private static readonly Version MinimumHelperVersion = new Version(0, 1, 5);
+
+    private enum Status
+    {
+        Pass,
+        Warn,
+        Fail
+    }
+
+    public static async Task<bool> RunAsync(CommandLineOptions options, string helperDirectory, bool isAdmin)
+    {
+        List<Status> statuses = new List<Status>();
+
+        void Report(Status status, string check, string detail)
+        {
+            statuses.Add(status);
+            Console.ForegroundColor = status switch
+            {
+                Status.Pass => ConsoleColor.Green,
+                Status.Warn => ConsoleColor.Yellow,
+                _ => ConsoleColor.Red
+            };
+            Console.Write($"[{status.ToString().ToUpperInvariant()}]");
+            Console.ResetColor();
+            Console.WriteLine($" {check}: {detail}");
+        }
+
+        Report(isAdmin ? Status.Pass : Status.Fail, "administrator", isAdmin
+            ? "running elevated"
+            : "not elevated, the benchmark needs 'Run as administrator'");
+
+        string setTimerResolution = Path.Combine(helperDirectory, "SetTimerResolution.exe");
+        if (!File.Exists(setTimerResolution))
+        {
+            Report(Status.Fail, "SetTimerResolution.exe", $"not found in {helperDirectory}");
+        }
+        else
+        {
+            (Status status, string version) = CheckVersion(setTimerResolution);
+            Report(status, "SetTimerResolution.exe", $"{setTimerResolution} ({version})");
+        }
+
+        string measureSleep = Path.Combine(helperDirectory, "MeasureSleep.exe");
+        if (!File.Exists(measureSleep))
+        {
+            Report(Status.Fail, "MeasureSleep.exe", $"not found in {helperDirectory}");
+        }
+        else
+        {
+            // A short run proves the helper starts and prints output this version can parse
+            (Status versionStatus, string version) = CheckVersion(measureSleep);
+            (Status runStatus, string detail) = await TryMeasureSleepAsync(measureSleep);
+            Report((Status)Math.Max((int)versionStatus, (int)runStatus), "MeasureSleep.exe",
+                $"{measureSleep} ({version}), {detail}");
+        }
+
+        try
+        {
+            BootConfiguration bootConfiguration = await BootConfiguration.QueryAsync();
+            Report(bootConfiguration.UsePlatformClock ? Status.Warn : Status.Pass, "HPET as platform clock",
+                bootConfiguration.UsePlatformClock ? "on (bcdedit useplatformclock)" : "off");
+            Report(bootConfiguration.DisableDynamicTick ? Status.Pass : Status.Warn, "dynamic tick",
+                bootConfiguration.DisableDynamicTick ? "disabled" : "enabled");
+        }
+        catch (Exception e) when (e is Win32Exception or InvalidOperationException)
+        {
+            Report(Status.Warn, "boot configuration", $"unavailable ({e.Message})");
+        }
+
+        // Balanced and power saver plans park cores and lower clocks between waits
+        string? powerPlan = await ActivePowerPlanAsync();
+        bool performancePlan = powerPlan?.Contains("performance", StringComparison.OrdinalIgnoreCase) == true;
+        Report(performancePlan ? Status.Pass : Status.Warn, "power plan", powerPlan ?? "unavailable");
+
+        Report(SystemInfo.HypervisorPresent ? Status.Warn : Status.Pass, "hypervisor",
+            SystemInfo.HypervisorPresent
+                ? $"present (VBS: {(SystemInfo.VbsEnabled ? "on" : "off")}, HVCI: {(SystemInfo.HvciEnabled ? "on" : "off")}), timer interrupts are virtualized"
+                : "not detected");
+
+        (bool globalResolution, string resolutionScope) = SystemInfo.TimerResolutionScope;
+        Report(globalResolution ? Status.Pass : Status.Warn, "timer resolution scope", resolutionScope);
+
+        TimerResolution? resolution = TimerResolution.Query();
+        Report(resolution is null ? Status.Warn : Status.Pass, "global timer resolution", resolution is { } current
+            ? $"{current.CurrentMs}ms (supported {current.FinestMs}ms to {current.CoarsestMs}ms)"
+            : "unavailable");
+
+        string outputDirectory = Path.GetDirectoryName(Path.GetFullPath(options.ResultsPath))!;
+        bool writable = IsWritable(outputDirectory);
+        Report(writable ? Status.Pass : Status.Fail, "output directory",
+            writable ? $"{outputDirectory} is writable" : $"{outputDirectory} is not writable");
+
+        Console.WriteLine();
+        Console.WriteLine(
+            $"{statuses.Count(s => s == Status.Pass)} passed, {statuses.Count(s => s == Status.Warn)} warnings, {statuses.Count(s => s == Status.Fail)} failed");
+
+        return !statuses.Contains(Status.Fail);
+    }
+
+    // A helper without version information cannot be checked, which is worth a warning rather than a failure
+    private static (Status Status, string Detail) CheckVersion(string path)
+    {
+        FileVersionInfo info = FileVersionInfo.GetVersionInfo(path);
+        if (info.FileVersion is null)
+        {
+            return (Status.Warn, "version unknown");
+        }
+
+        Version version = new Version(info.FileMajorPart, info.FileMinorPart, info.FileBuildPart);
+        return version < MinimumHelperVersion
+            ? (Status.Fail, $"version {info.FileVersion}, older than the required {MinimumHelperVersion}")
+            : (Status.Pass, $"version {info.FileVersion}");
+    }
+
+    private static async Task<(Status Status, string Detail)> TryMeasureSleepAsync(string measureSleep)
+    {
+        ProcessStartInfo startInfo = new ProcessStartInfo
+        {
+            FileName = measureSleep,
+            Arguments = "--samples 10",
+            UseShellExecute = false,
+            RedirectStandardOutput = true
+        };
+
+        try
+        {
+            using Process process = Process.Start(startInfo)
+                                    ?? throw new InvalidOperationException("MeasureSleep.exe failed to start");
+            string output = await process.StandardOutput.ReadToEndAsync();
+            await process.WaitForExitAsync();
+
+            Measurement measurement = Measurement.Parse(output, 0);
+            return measurement.IsValid
+                ? (Status.Pass, $"test run measured a {NumberFormat.Round(measurement.Avg)}ms delta")
+                : (Status.Fail, "test run printed nothing this version can parse");
+        }
+        catch (Exception e) when (e is Win32Exception or InvalidOperationException)
+        {
+            return (Status.Fail, $"test run failed: {e.Message}");
+        }
+    }
+
+    // "Power Scheme GUID: 381b4222-f694-41f0-9685-ff5bb260df2e  (Balanced)" gives "Balanced"
+    private static async Task<string?> ActivePowerPlanAsync()
+    {
+        ProcessStartInfo startInfo = new ProcessStartInfo
+        {
+            FileName = "powercfg",
+            Arguments = "/getactivescheme",
+            UseShellExecute = false,
+            RedirectStandardOutput = true
+        };
+
+        try
+        {
+            using Process process = Process.Start(startInfo)
+                                    ?? throw new InvalidOperationException("powercfg failed to start");
+            string output = await process.StandardOutput.ReadToEndAsync();
+            await process.WaitForExitAsync();
+
+            int open = output.LastIndexOf('(');
+            int close = output.LastIndexOf(')');
+            return open >= 0 && close > open ? output[(open + 1)..close] : null;
+        }
+        catch (Exception e) when (e is Win32Exception or InvalidOperationException)
+        {
+            return null;
+        }
+    }
+
+    private static bool IsWritable(string directory)
+    {
+        string probe = Path.Combine(directory, $".timerbenchmark-doctor-{Environment.ProcessId}");
+
+        try
+        {
+            File.WriteAllText(probe, string.Empty);
+            File.Delete(probe);
+            return true;
+        }
+        catch (Exception e) when (e is IOException or UnauthorizedAccessException)
+        {
+            return false;
+        }
+    }
+}
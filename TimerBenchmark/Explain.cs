@@ -0,0 +1,15 @@
+﻿namespace TimerBenchmark;
+
+// Prints the reasoning behind each decision when --explain is passed
+internal static class Explain
+{
+    public static bool Enabled { get; set; }
+
+    public static void Write(string rationale)
+    {
+        if (Enabled)
+        {
+            Console.WriteLine($"explain: {rationale}");
+        }
+    }
+}
@@ -0,0 +1,15 @@
+﻿using System.Text;
+
+namespace TimerBenchmark;
+
+// Status symbols, with ASCII stand-ins for consoles whose code page cannot render them
+internal static class Glyphs
+{
+    public static bool Ascii { get; set; }
+
+    // Legacy conhost code pages such as 437 or 1252 show the Unicode symbols as boxes or question marks
+    public static bool ConsoleSupportsUnicode => Console.OutputEncoding.CodePage == Encoding.UTF8.CodePage;
+
+    public static string Ok => Ascii ? "[OK]" : "✓";
+    public static string Failed => Ascii ? "[X]" : "✗";
+}
@@ -0,0 +1,30 @@
+﻿using System.Globalization;
+
+namespace TimerBenchmark;
+
+internal static class History
+{
+    private const string CsvHeader = "Timestamp,CPU,WindowsBuild,OptimalResolutionMs,OptimalDeltaMs,OptimalSTDEV";
+
+    public static async Task AppendAsync(string path, BenchmarkReport report)
+    {
+        if (report.Optimal is not { } optimal)
+        {
+            return;
+        }
+
+        if (!File.Exists(path))
+        {
+            await File.WriteAllTextAsync(path, CsvHeader + Environment.NewLine);
+        }
+
+        string timestamp = DateTime.Now.ToString("s", CultureInfo.InvariantCulture);
+        // CPU brand strings can contain commas, so quote the field
+        string cpu = $"\"{SystemInfo.CpuName.Replace("\"", "\"\"")}\"";
+        string row =
+            $"{timestamp},{cpu},{SystemInfo.WindowsBuild},{optimal.ResolutionMs:R},{optimal.DeltaMs:R},{optimal.Stdev:R}";
+
+        await File.AppendAllTextAsync(path, row + Environment.NewLine);
+        Console.WriteLine($"info: run summary appended to {path}");
+    }
+}
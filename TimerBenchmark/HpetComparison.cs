@@ -0,0 +1,90 @@
+﻿using System.Text;
+using System.Text.Json;
+
+namespace TimerBenchmark;
+
+// Persists the first run across the reboot needed to toggle HPET, then compares the second run against it
+internal static class HpetComparison
+{
+    public const string StatePath = "compare-hpet.json";
+
+    public static async Task SaveAsync(BootConfiguration bootConfiguration, BenchmarkReport report)
+    {
+        using MemoryStream buffer = new MemoryStream();
+        using (Utf8JsonWriter json = new Utf8JsonWriter(buffer, new JsonWriterOptions { Indented = true }))
+        {
+            json.WriteStartObject();
+            json.WriteBoolean("usePlatformClock", bootConfiguration.UsePlatformClock);
+            json.WriteBoolean("disableDynamicTick", bootConfiguration.DisableDynamicTick);
+            json.WritePropertyName("report");
+            json.WriteRawValue(report.ToJson());
+            json.WriteEndObject();
+        }
+
+        await File.WriteAllTextAsync(StatePath, Encoding.UTF8.GetString(buffer.ToArray()));
+
+        Console.WriteLine($"info: first run saved in {StatePath}");
+        Console.WriteLine(
+            $"info: now {ToggleCommand(bootConfiguration.UsePlatformClock)}, reboot, then run again with '--compare-hpet continue'");
+    }
+
+    private static string ToggleCommand(bool usePlatformClock)
+    {
+        return usePlatformClock
+            ? "run 'bcdedit /deletevalue useplatformclock' as administrator"
+            : "run 'bcdedit /set useplatformclock true' as administrator";
+    }
+
+    // Returns the relative improvement of the optimal delta in percent, or null when the comparison is not possible
+    public static async Task<double?> CompareAsync(BootConfiguration bootConfiguration, BenchmarkReport report)
+    {
+        if (!File.Exists(StatePath))
+        {
+            await Console.Error.WriteLineAsync(
+                $"error: {StatePath} not found, start the comparison with '--compare-hpet' first");
+            return null;
+        }
+
+        bool previousPlatformClock;
+        double previousDelta;
+        try
+        {
+            using JsonDocument document = JsonDocument.Parse(await File.ReadAllTextAsync(StatePath));
+            previousPlatformClock = document.RootElement.GetProperty("usePlatformClock").GetBoolean();
+            previousDelta = document.RootElement.GetProperty("report").GetProperty("optimal").GetProperty("deltaMs")
+                .GetDouble();
+        }
+        catch (Exception e) when (e is JsonException or KeyNotFoundException or InvalidOperationException)
+        {
+            await Console.Error.WriteLineAsync($"error: {StatePath} is not a valid comparison state: {e.Message}");
+            return null;
+        }
+
+        if (report.Optimal is not { } optimal)
+        {
+            return null;
+        }
+
+        // Both runs used the same clock, so there is nothing to compare; the state stays for the run after the reboot
+        if (previousPlatformClock == bootConfiguration.UsePlatformClock)
+        {
+            await Console.Error.WriteLineAsync(
+                $"error: the platform clock setting did not change since the first run, {ToggleCommand(previousPlatformClock)}, reboot and run '--compare-hpet continue' again ({StatePath} was kept)");
+            return null;
+        }
+
+        (double hpetOn, double hpetOff) = previousPlatformClock
+            ? (previousDelta, optimal.DeltaMs)
+            : (optimal.DeltaMs, previousDelta);
+        double improvement = hpetOn > 0 ? (hpetOn - hpetOff) / hpetOn * 100 : 0;
+
+        Console.WriteLine();
+        Console.WriteLine($"HPET on:  optimal delta {NumberFormat.Round(hpetOn)}ms");
+        Console.WriteLine($"HPET off: optimal delta {NumberFormat.Round(hpetOff)}ms");
+        Console.WriteLine(improvement >= 0
+            ? $"Disabling HPET improved the optimal delta by {improvement:0.##}%"
+            : $"Disabling HPET worsened the optimal delta by {-improvement:0.##}%");
+
+        return improvement;
+    }
+}
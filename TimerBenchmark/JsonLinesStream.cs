@@ -0,0 +1,107 @@
+﻿using System.Text;
+using System.Text.Json;
+
+namespace TimerBenchmark;
+
+internal sealed class JsonLinesStream : IAsyncDisposable
+{
+    private readonly TextWriter _writer;
+    private readonly bool _ownsWriter;
+
+    private JsonLinesStream(TextWriter writer, bool ownsWriter)
+    {
+        _writer = writer;
+        _ownsWriter = ownsWriter;
+    }
+
+    public static JsonLinesStream Open(string? path, TextWriter stdout)
+    {
+        return path is null
+            ? new JsonLinesStream(stdout, ownsWriter: false)
+            : new JsonLinesStream(new StreamWriter(path, append: false), ownsWriter: true);
+    }
+
+    public static string FormatError(string error, string detail)
+    {
+        return Format(json =>
+        {
+            json.WriteString("error", error);
+            json.WriteString("detail", detail);
+        });
+    }
+
+    // One-line result for wrapper scripts; hpetEnabled is null when the boot configuration could not be read, and
+    // error names the failure when the run stopped before it could measure or report anything
+    public static string FormatSummary(bool success, BenchmarkResult? optimal, int iterations, TimeSpan elapsed,
+        bool? hpetEnabled, string? error = null)
+    {
+        return Format(json =>
+        {
+            json.WriteBoolean("success", success);
+            if (error is not null)
+            {
+                json.WriteString("error", error);
+            }
+
+            json.WritePropertyName("optimal");
+            if (optimal is { } result)
+            {
+                json.WriteStartObject();
+                json.WriteNumber("resolutionMs", result.ResolutionMs);
+                json.WriteNumber("deltaMs", result.DeltaMs);
+                json.WriteNumber("stdev", result.Stdev);
+                json.WriteEndObject();
+            }
+            else
+            {
+                json.WriteNullValue();
+            }
+
+            json.WriteNumber("iterations", iterations);
+            json.WriteNumber("elapsedSeconds", Math.Round(elapsed.TotalSeconds, 1));
+            if (hpetEnabled is { } hpet)
+            {
+                json.WriteBoolean("hpetEnabled", hpet);
+            }
+            else
+            {
+                json.WriteNull("hpetEnabled");
+            }
+        });
+    }
+
+    public async Task WriteAsync(double resolutionMs, double deltaMs, double stdev)
+    {
+        string line = Format(json =>
+        {
+            json.WriteNumber("resolutionMs", resolutionMs);
+            json.WriteNumber("deltaMs", deltaMs);
+            json.WriteNumber("stdev", stdev);
+        });
+
+        // Flush every line so consumers tailing the stream see each measurement as soon as it completes
+        await _writer.WriteLineAsync(line);
+        await _writer.FlushAsync();
+    }
+
+    private static string Format(Action<Utf8JsonWriter> writeProperties)
+    {
+        using MemoryStream buffer = new MemoryStream();
+        using (Utf8JsonWriter json = new Utf8JsonWriter(buffer))
+        {
+            json.WriteStartObject();
+            writeProperties(json);
+            json.WriteEndObject();
+        }
+
+        return Encoding.UTF8.GetString(buffer.ToArray());
+    }
+
+    public async ValueTask DisposeAsync()
+    {
+        if (_ownsWriter)
+        {
+            await _writer.DisposeAsync();
+        }
+    }
+}
@@ -0,0 +1,54 @@
+﻿namespace TimerBenchmark;
+
+// Redraws a horizontal bar chart of delta per resolution after every measurement
+internal sealed class LiveChart
+{
+    private const int LabelWidth = 10;
+
+    private readonly List<BenchmarkResult> _results = new List<BenchmarkResult>();
+    private readonly OptimalMetric _optimalBy;
+    private readonly string _total;
+
+    // Adaptive sweeps stop refining early, so their total is only the worst case
+    public LiveChart(OptimalMetric optimalBy, int total, bool totalIsUpperBound)
+    {
+        _optimalBy = optimalBy;
+        _total = totalIsUpperBound ? $"up to {total}" : $"{total}";
+    }
+
+    public static bool IsSupported => !Console.IsOutputRedirected;
+
+    public void Update(BenchmarkResult result, double requestedValue)
+    {
+        _results.Add(result);
+
+        int width = Math.Max(Console.WindowWidth, LabelWidth + 20);
+        int height = Math.Max(Console.WindowHeight - 4, 1);
+        int barWidth = width - LabelWidth - 12;
+        double maxDelta = _results.Max(r => r.DeltaMs);
+        // The same selection as the final report, so the live best never disagrees with the optimal
+        BenchmarkReport report = new BenchmarkReport(_results, _optimalBy);
+
+        Console.Clear();
+        Console.WriteLine(
+            $"benchmarked {requestedValue}ms (effective: {result.Resolution / 1E4}ms), {_results.Count} of {_total}");
+        if (report.Optimal is { } best)
+        {
+            Console.WriteLine(
+                $"current best: {best.ResolutionMs}ms (delta: {NumberFormat.Round(best.DeltaMs)}ms, STDEV: {NumberFormat.Round(best.Stdev)})");
+        }
+
+        Console.WriteLine();
+
+        // Only the most recent rows fit on screen; duplicate rows are told apart by position
+        for (int i = Math.Max(_results.Count - height, 0); i < _results.Count; i++)
+        {
+            BenchmarkResult row = _results[i];
+            int length = maxDelta > 0 ? (int)Math.Round(row.DeltaMs / maxDelta * barWidth) : 0;
+            ConsoleColor previous = Console.ForegroundColor;
+            Console.ForegroundColor = i == report.OptimalIndex ? ConsoleColor.Green : previous;
+            Console.WriteLine($"{row.ResolutionMs,LabelWidth:F4} {new string('#', Math.Max(length, 0))} {row.DeltaMs.ToString(NumberFormat.Fixed)}");
+            Console.ForegroundColor = previous;
+        }
+    }
+}
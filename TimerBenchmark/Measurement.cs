@@ -0,0 +1,214 @@
+﻿using System.Diagnostics.CodeAnalysis;
+using System.Globalization;
+using System.Text.Json;
+
+namespace TimerBenchmark;
+
+internal sealed class Measurement
+{
+    // MeasureSleep drops its first sample before computing Avg/STDEV, so recomputed statistics do the same
+    private const int MeasureSleepDiscardedSamples = 1;
+
+    public double Avg { get; private init; }
+    public double Stdev { get; private init; }
+    public IReadOnlyList<double> Samples { get; private init; } = Array.Empty<double>();
+
+    // Sleep latency is skewed by occasional spikes, so the median shows what a typical wait looks like
+    public double? Median
+    {
+        get
+        {
+            if (Samples.Count == 0)
+            {
+                return null;
+            }
+
+            List<double> sorted = Samples.Order().ToList();
+            int middle = sorted.Count / 2;
+            return sorted.Count % 2 == 1 ? sorted[middle] : (sorted[middle - 1] + sorted[middle]) / 2;
+        }
+    }
+
+    public double? Min => Samples.Count > 0 ? Samples.Min() : null;
+    public double? Max => Samples.Count > 0 ? Samples.Max() : null;
+
+    // Samples were asked to be discarded, but without per-sample values the reported statistics still include them
+    public bool DiscardIgnored { get; private init; }
+
+    // MeasureSleep prints nothing parseable when it fails, which leaves both statistics at zero
+    public bool IsValid => Avg != 0 || Stdev != 0;
+
+    public static Measurement Parse(string output, int discardSamples)
+    {
+        if (TryParseJson(output, discardSamples, out Measurement? fromJson))
+        {
+            return fromJson;
+        }
+
+        string[] outputLines = output.Split(new[] { Environment.NewLine }, StringSplitOptions.RemoveEmptyEntries);
+        (double avg, double stdev) = (0, 0);
+        List<double> samples = new List<double>();
+
+        foreach (var line in outputLines)
+        {
+            if (line.StartsWith("Avg: ") && TryParseNumber(line.AsSpan(5), out var parsedAvg))
+            {
+                avg = parsedAvg;
+            }
+            else if (line.StartsWith("STDEV: ") && TryParseNumber(line.AsSpan(7), out var parsedStdev))
+            {
+                stdev = parsedStdev;
+            }
+            else if (TryParseSampleDelta(line, out var delta))
+            {
+                samples.Add(delta);
+            }
+        }
+
+        return FromReported(avg, stdev, samples, discardSamples);
+    }
+
+    // MeasureSleep has no option to drop extra samples itself, so recompute from the per-sample values
+    private static Measurement FromReported(double avg, double stdev, List<double> samples, int discardSamples)
+    {
+        int skipped = MeasureSleepDiscardedSamples + discardSamples;
+        List<double> kept = samples.Count > skipped
+            ? samples.GetRange(skipped, samples.Count - skipped)
+            : new List<double>();
+
+        if (discardSamples > 0 && kept.Count > 0)
+        {
+            return FromSamples(kept);
+        }
+
+        return new Measurement
+        {
+            Avg = avg, Stdev = stdev, Samples = kept, DiscardIgnored = discardSamples > 0 && (avg != 0 || stdev != 0)
+        };
+    }
+
+    public static Measurement FromSamples(IReadOnlyList<double> samples)
+    {
+        if (samples.Count == 0)
+        {
+            return new Measurement();
+        }
+
+        double avg = samples.Average();
+        double stdev = Math.Sqrt(samples.Average(sample => Math.Pow(sample - avg, 2)));
+
+        return new Measurement { Avg = avg, Stdev = stdev, Samples = samples };
+    }
+
+    // One delta in ms per line from an external measurement tool; blank lines and "#" comments are skipped
+    public static Measurement ParseRawSamples(string text)
+    {
+        List<double> samples = new List<double>();
+        string[] lines = text.Split('\n');
+
+        for (int i = 0; i < lines.Length; i++)
+        {
+            string line = lines[i].Trim();
+            if (line.Length == 0 || line.StartsWith('#'))
+            {
+                continue;
+            }
+
+            if (!TryParseNumber(line, out double sample))
+            {
+                throw new FormatException($"line {i + 1} is not a number: '{line}'");
+            }
+
+            samples.Add(sample);
+        }
+
+        return FromSamples(samples);
+    }
+
+    // Drops the lowest and highest percent of samples so a single scheduling hiccup does not skew the statistics
+    public Measurement Trim(double percent)
+    {
+        int drop = (int)(Samples.Count * percent / 100);
+        return drop == 0
+            ? this
+            : FromSamples(Samples.Order().Skip(drop).Take(Samples.Count - 2 * drop).ToList());
+    }
+
+    public static Measurement Aggregate(IReadOnlyList<Measurement> measurements, RetryAggregation aggregation)
+    {
+        List<Measurement> ordered = measurements.OrderBy(m => m.Avg).ThenBy(m => m.Stdev).ToList();
+
+        return aggregation switch
+        {
+            RetryAggregation.Best => ordered[0],
+            RetryAggregation.Median => ordered[(ordered.Count - 1) / 2],
+            _ => measurements[0]
+        };
+    }
+
+    // A MeasureSleep that prints {"avg": ..., "stdev": ..., "samples": [...]} is read without relying on the text
+    // format; anything else falls back to the line prefixes
+    private static bool TryParseJson(string output, int discardSamples, [NotNullWhen(true)] out Measurement? measurement)
+    {
+        measurement = null;
+
+        if (!output.TrimStart().StartsWith('{'))
+        {
+            return false;
+        }
+
+        try
+        {
+            using JsonDocument document = JsonDocument.Parse(output);
+            JsonElement root = document.RootElement;
+
+            if (!root.TryGetProperty("avg", out JsonElement avg) || !root.TryGetProperty("stdev", out JsonElement stdev))
+            {
+                return false;
+            }
+
+            List<double> samples = root.TryGetProperty("samples", out JsonElement array)
+                                   && array.ValueKind == JsonValueKind.Array
+                ? array.EnumerateArray().Select(sample => sample.GetDouble()).ToList()
+                : new List<double>();
+
+            measurement = FromReported(avg.GetDouble(), stdev.GetDouble(), samples, discardSamples);
+            return true;
+        }
+        catch (Exception e) when (e is JsonException or InvalidOperationException or FormatException)
+        {
+            return false;
+        }
+    }
+
+    // Per-sample lines look like "Resolution: 0.5000ms, Sleep(1) slept 1.0123ms (delta: 0.0123)"
+    private static bool TryParseSampleDelta(string line, out double delta)
+    {
+        delta = 0;
+
+        int start = line.IndexOf("(delta: ", StringComparison.Ordinal);
+        if (start < 0)
+        {
+            return false;
+        }
+
+        start += "(delta: ".Length;
+        int end = line.IndexOf(')', start);
+
+        return end > start && TryParseNumber(line.AsSpan(start, end - start), out delta);
+    }
+
+    // A decimal comma from a localized MeasureSleep build must not be read as a thousands separator
+    private static bool TryParseNumber(ReadOnlySpan<char> text, out double value)
+    {
+        return double.TryParse(text.ToString().Replace(',', '.'), NumberStyles.Float, CultureInfo.InvariantCulture,
+            out value);
+    }
+}
+
+internal enum RetryAggregation
+{
+    First,
+    Best,
+    Median
+}
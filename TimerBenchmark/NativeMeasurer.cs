@@ -0,0 +1,151 @@
+﻿using System.ComponentModel;
+using System.Diagnostics;
+using System.Runtime.InteropServices;
+
+namespace TimerBenchmark;
+
+// Measures wait overshoot in-process for durations MeasureSleep cannot express, since Sleep only takes whole milliseconds
+internal static class NativeMeasurer
+{
+    private const uint TimerAllAccess = 0x1F0003;
+    private const uint Infinite = 0xFFFFFFFF;
+    private const uint CreateWaitableTimerHighResolution = 0x00000002;
+
+    // Adaptive sampling needs a few samples before the standard error of the mean is meaningful
+    private const int MinAdaptiveSamples = 10;
+
+    public static Measurement Measure(double durationMs, int samples, int discardSamples, Action? onSample = null)
+    {
+        return Run(durationMs, discardSamples, deltas => deltas.Count < samples, onSample: onSample);
+    }
+
+    // Same as Measure, but on a CREATE_WAITABLE_TIMER_HIGH_RESOLUTION timer, which is not bound to the global timer resolution
+    public static Measurement MeasureHighResolution(double durationMs, int samples, int discardSamples)
+    {
+        return Run(durationMs, discardSamples, deltas => deltas.Count < samples, CreateWaitableTimerHighResolution);
+    }
+
+    // Keeps sampling until the standard error of the mean drops below targetSem, capped by maxSamples
+    public static Measurement MeasureAdaptive(double durationMs, double targetSem, int maxSamples, int discardSamples,
+        Action? onSample = null)
+    {
+        return Run(durationMs, discardSamples, deltas =>
+            deltas.Count < Math.Min(MinAdaptiveSamples, maxSamples)
+            || (deltas.Count < maxSamples && StandardError(deltas) > targetSem), onSample: onSample);
+    }
+
+    // Keeps sampling until the time budget is spent so every resolution gets the same measurement effort
+    public static Measurement MeasureFor(double durationMs, TimeSpan budget, int discardSamples, Action? onSample = null)
+    {
+        long? start = null;
+
+        return Run(durationMs, discardSamples, deltas =>
+        {
+            start ??= Stopwatch.GetTimestamp();
+            return deltas.Count == 0 || Stopwatch.GetElapsedTime(start.Value) < budget;
+        }, onSample: onSample);
+    }
+
+    // Runs the measurement on a dedicated thread restricted to the lowest processor in the affinity mask
+    public static Measurement MeasurePinned(ulong affinityMask, double durationMs, int samples, int discardSamples)
+    {
+        ulong processor = affinityMask & (~affinityMask + 1);
+        Measurement? measurement = null;
+        Exception? failure = null;
+
+        Thread thread = new Thread(() =>
+        {
+            try
+            {
+                if (SetThreadAffinityMask(GetCurrentThread(), (UIntPtr)processor) == UIntPtr.Zero)
+                {
+                    throw new Win32Exception(Marshal.GetLastWin32Error());
+                }
+
+                measurement = Measure(durationMs, samples, discardSamples);
+            }
+            catch (Exception e)
+            {
+                failure = e;
+            }
+        });
+        thread.Start();
+        thread.Join();
+
+        return measurement ?? throw new InvalidOperationException($"pinned measurement failed: {failure?.Message}", failure);
+    }
+
+    private static Measurement Run(double durationMs, int discardSamples, Func<List<double>, bool> needsMoreSamples,
+        uint flags = 0, Action? onSample = null)
+    {
+        IntPtr timer = CreateWaitableTimerExW(IntPtr.Zero, null, flags, TimerAllAccess);
+        if (timer == IntPtr.Zero)
+        {
+            throw new Win32Exception(Marshal.GetLastWin32Error());
+        }
+
+        try
+        {
+            // Negative due times are relative, in 100ns units
+            long dueTime = -(long)Math.Round(durationMs * 1E4);
+
+            for (int i = 0; i < discardSamples; i++)
+            {
+                Wait(timer, dueTime, durationMs);
+            }
+
+            List<double> deltas = new List<double>();
+            while (needsMoreSamples(deltas))
+            {
+                deltas.Add(Wait(timer, dueTime, durationMs));
+                onSample?.Invoke();
+            }
+
+            return Measurement.FromSamples(deltas);
+        }
+        finally
+        {
+            CloseHandle(timer);
+        }
+    }
+
+    private static double Wait(IntPtr timer, long dueTime, double durationMs)
+    {
+        long start = Stopwatch.GetTimestamp();
+
+        if (!SetWaitableTimer(timer, ref dueTime, 0, IntPtr.Zero, IntPtr.Zero, false))
+        {
+            throw new Win32Exception(Marshal.GetLastWin32Error());
+        }
+
+        WaitForSingleObject(timer, Infinite);
+        return Stopwatch.GetElapsedTime(start).TotalMilliseconds - durationMs;
+    }
+
+    private static double StandardError(List<double> deltas)
+    {
+        double avg = deltas.Average();
+        double stdev = Math.Sqrt(deltas.Average(delta => Math.Pow(delta - avg, 2)));
+        return stdev / Math.Sqrt(deltas.Count);
+    }
+
+    [DllImport("kernel32.dll", SetLastError = true, CharSet = CharSet.Unicode)]
+    private static extern IntPtr CreateWaitableTimerExW(IntPtr timerAttributes, string? timerName, uint flags,
+        uint desiredAccess);
+
+    [DllImport("kernel32.dll", SetLastError = true)]
+    private static extern bool SetWaitableTimer(IntPtr timer, ref long dueTime, int period,
+        IntPtr completionRoutine, IntPtr argToCompletionRoutine, bool resume);
+
+    [DllImport("kernel32.dll", SetLastError = true)]
+    private static extern uint WaitForSingleObject(IntPtr handle, uint milliseconds);
+
+    [DllImport("kernel32.dll", SetLastError = true)]
+    private static extern bool CloseHandle(IntPtr handle);
+
+    [DllImport("kernel32.dll")]
+    private static extern IntPtr GetCurrentThread();
+
+    [DllImport("kernel32.dll", SetLastError = true)]
+    private static extern UIntPtr SetThreadAffinityMask(IntPtr thread, UIntPtr threadAffinityMask);
+}
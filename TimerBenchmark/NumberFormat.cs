@@ -0,0 +1,16 @@
+﻿namespace TimerBenchmark;
+
+// Decimal places for every delta and STDEV that is printed, set with --precision; files keep full precision
+internal static class NumberFormat
+{
+    public const int DefaultDigits = 4;
+
+    public static int Digits { get; set; } = DefaultDigits;
+
+    public static string Fixed => $"F{Digits}";
+
+    public static double Round(double value)
+    {
+        return Math.Round(value, Digits);
+    }
+}
@@ -0,0 +1,82 @@
+﻿using System.Diagnostics;
+
+namespace TimerBenchmark;
+
+// Where each iteration spends its time, printed when --timing is passed. Phases are timed from a
+// Stopwatch.GetTimestamp() taken before the work, so callers need no bookkeeping when timing is off
+internal static class PhaseTiming
+{
+    private static readonly Dictionary<string, TimeSpan> Iteration = new Dictionary<string, TimeSpan>();
+    private static readonly Dictionary<string, TimeSpan> Totals = new Dictionary<string, TimeSpan>();
+
+    // Phases in the order they were first recorded, so the report follows the iteration
+    private static readonly List<string> Order = new List<string>();
+
+    // Zero outside an iteration, so warmup cycles are not counted
+    private static long _iterationStart;
+    private static TimeSpan _total;
+    private static int _iterations;
+
+    public static bool Enabled { get; set; }
+
+    public static void BeginIteration()
+    {
+        Iteration.Clear();
+        _iterationStart = Stopwatch.GetTimestamp();
+    }
+
+    // Retries record the same phase again, which adds to its time for the iteration
+    public static void Record(string phase, long startTimestamp)
+    {
+        if (!Enabled || _iterationStart == 0)
+        {
+            return;
+        }
+
+        TimeSpan elapsed = Stopwatch.GetElapsedTime(startTimestamp);
+        if (!Order.Contains(phase))
+        {
+            Order.Add(phase);
+        }
+
+        Iteration[phase] = Iteration.GetValueOrDefault(phase) + elapsed;
+        Totals[phase] = Totals.GetValueOrDefault(phase) + elapsed;
+    }
+
+    public static void EndIteration(double resolutionMs)
+    {
+        if (!Enabled)
+        {
+            return;
+        }
+
+        TimeSpan elapsed = Stopwatch.GetElapsedTime(_iterationStart);
+        _iterationStart = 0;
+        _total += elapsed;
+        _iterations++;
+
+        string phases = string.Join(", ", Order.Where(Iteration.ContainsKey)
+            .Select(phase => $"{phase} {Iteration[phase].TotalMilliseconds:F1}ms"));
+        Console.WriteLine($"timing: {resolutionMs}ms took {elapsed.TotalMilliseconds:F1}ms ({phases})");
+    }
+
+    public static void Report()
+    {
+        if (!Enabled || _iterations == 0)
+        {
+            return;
+        }
+
+        Console.WriteLine($"timing: {_iterations} iterations took {_total.TotalSeconds:F1}s");
+        foreach (string phase in Order)
+        {
+            TimeSpan time = Totals[phase];
+            Console.WriteLine(
+                $"timing:   {phase,-8} {time.TotalSeconds,8:F2}s {time / _total,7:P1}  avg {time.TotalMilliseconds / _iterations:F1}ms");
+        }
+
+        // Console output, extra measurements and anything else between the timed phases
+        TimeSpan other = _total - Totals.Values.Aggregate(TimeSpan.Zero, (sum, time) => sum + time);
+        Console.WriteLine($"timing:   {"other",-8} {other.TotalSeconds,8:F2}s {other / _total,7:P1}");
+    }
+}
@@ -0,0 +1,223 @@
+﻿using System.Buffers.Binary;
+using System.IO.Compression;
+using System.Text;
+
+namespace TimerBenchmark;
+
+// Minimal RGB raster with a built-in 5x7 bitmap font, encoded as PNG without any imaging dependency
+internal sealed class PngCanvas
+{
+    public const int GlyphWidth = 6;
+    public const int GlyphHeight = 8;
+
+    private static readonly Dictionary<char, string[]> Font = new Dictionary<char, string[]>
+    {
+        [' '] = new[] { "00000", "00000", "00000", "00000", "00000", "00000", "00000" },
+        ['0'] = new[] { "01110", "10001", "10011", "10101", "11001", "10001", "01110" },
+        ['1'] = new[] { "00100", "01100", "00100", "00100", "00100", "00100", "01110" },
+        ['2'] = new[] { "01110", "10001", "00001", "00010", "00100", "01000", "11111" },
+        ['3'] = new[] { "11111", "00010", "00100", "00010", "00001", "10001", "01110" },
+        ['4'] = new[] { "00010", "00110", "01010", "10010", "11111", "00010", "00010" },
+        ['5'] = new[] { "11111", "10000", "11110", "00001", "00001", "10001", "01110" },
+        ['6'] = new[] { "00110", "01000", "10000", "11110", "10001", "10001", "01110" },
+        ['7'] = new[] { "11111", "00001", "00010", "00100", "01000", "01000", "01000" },
+        ['8'] = new[] { "01110", "10001", "10001", "01110", "10001", "10001", "01110" },
+        ['9'] = new[] { "01110", "10001", "10001", "01111", "00001", "00010", "01100" },
+        ['A'] = new[] { "01110", "10001", "10001", "11111", "10001", "10001", "10001" },
+        ['B'] = new[] { "11110", "10001", "10001", "11110", "10001", "10001", "11110" },
+        ['C'] = new[] { "01110", "10001", "10000", "10000", "10000", "10001", "01110" },
+        ['D'] = new[] { "11100", "10010", "10001", "10001", "10001", "10010", "11100" },
+        ['E'] = new[] { "11111", "10000", "10000", "11110", "10000", "10000", "11111" },
+        ['F'] = new[] { "11111", "10000", "10000", "11110", "10000", "10000", "10000" },
+        ['G'] = new[] { "01110", "10001", "10000", "10111", "10001", "10001", "01111" },
+        ['H'] = new[] { "10001", "10001", "10001", "11111", "10001", "10001", "10001" },
+        ['I'] = new[] { "01110", "00100", "00100", "00100", "00100", "00100", "01110" },
+        ['J'] = new[] { "00111", "00010", "00010", "00010", "00010", "10010", "01100" },
+        ['K'] = new[] { "10001", "10010", "10100", "11000", "10100", "10010", "10001" },
+        ['L'] = new[] { "10000", "10000", "10000", "10000", "10000", "10000", "11111" },
+        ['M'] = new[] { "10001", "11011", "10101", "10101", "10001", "10001", "10001" },
+        ['N'] = new[] { "10001", "10001", "11001", "10101", "10011", "10001", "10001" },
+        ['O'] = new[] { "01110", "10001", "10001", "10001", "10001", "10001", "01110" },
+        ['P'] = new[] { "11110", "10001", "10001", "11110", "10000", "10000", "10000" },
+        ['Q'] = new[] { "01110", "10001", "10001", "10001", "10101", "10010", "01101" },
+        ['R'] = new[] { "11110", "10001", "10001", "11110", "10100", "10010", "10001" },
+        ['S'] = new[] { "01111", "10000", "10000", "01110", "00001", "00001", "11110" },
+        ['T'] = new[] { "11111", "00100", "00100", "00100", "00100", "00100", "00100" },
+        ['U'] = new[] { "10001", "10001", "10001", "10001", "10001", "10001", "01110" },
+        ['V'] = new[] { "10001", "10001", "10001", "10001", "10001", "01010", "00100" },
+        ['W'] = new[] { "10001", "10001", "10001", "10101", "10101", "10101", "01010" },
+        ['X'] = new[] { "10001", "10001", "01010", "00100", "01010", "10001", "10001" },
+        ['Y'] = new[] { "10001", "10001", "10001", "01010", "00100", "00100", "00100" },
+        ['Z'] = new[] { "11111", "00001", "00010", "00100", "01000", "10000", "11111" },
+        ['.'] = new[] { "00000", "00000", "00000", "00000", "00000", "01100", "01100" },
+        [','] = new[] { "00000", "00000", "00000", "00000", "01100", "00100", "01000" },
+        [':'] = new[] { "00000", "01100", "01100", "00000", "01100", "01100", "00000" },
+        ['-'] = new[] { "00000", "00000", "00000", "11111", "00000", "00000", "00000" },
+        ['+'] = new[] { "00000", "00100", "00100", "11111", "00100", "00100", "00000" },
+        ['='] = new[] { "00000", "00000", "11111", "00000", "11111", "00000", "00000" },
+        ['('] = new[] { "00010", "00100", "01000", "01000", "01000", "00100", "00010" },
+        [')'] = new[] { "01000", "00100", "00010", "00010", "00010", "00100", "01000" },
+        ['<'] = new[] { "00010", "00100", "01000", "10000", "01000", "00100", "00010" },
+        ['>'] = new[] { "01000", "00100", "00010", "00001", "00010", "00100", "01000" },
+        ['/'] = new[] { "00000", "00001", "00010", "00100", "01000", "10000", "00000" },
+        ['%'] = new[] { "11000", "11001", "00010", "00100", "01000", "10011", "00011" },
+        ['|'] = new[] { "00100", "00100", "00100", "00100", "00100", "00100", "00100" },
+        ['_'] = new[] { "00000", "00000", "00000", "00000", "00000", "00000", "11111" }
+    };
+
+    private static readonly uint[] CrcTable = BuildCrcTable();
+
+    private readonly byte[] _pixels;
+
+    public PngCanvas(int width, int height, (byte R, byte G, byte B) background)
+    {
+        Width = width;
+        Height = height;
+        _pixels = new byte[width * height * 3];
+        FillRect(0, 0, width, height, background);
+    }
+
+    public int Width { get; }
+    public int Height { get; }
+
+    public void SetPixel(int x, int y, (byte R, byte G, byte B) color)
+    {
+        if (x < 0 || y < 0 || x >= Width || y >= Height)
+        {
+            return;
+        }
+
+        int offset = (y * Width + x) * 3;
+        _pixels[offset] = color.R;
+        _pixels[offset + 1] = color.G;
+        _pixels[offset + 2] = color.B;
+    }
+
+    public void FillRect(int x, int y, int width, int height, (byte R, byte G, byte B) color)
+    {
+        for (int row = y; row < y + height; row++)
+        {
+            for (int column = x; column < x + width; column++)
+            {
+                SetPixel(column, row, color);
+            }
+        }
+    }
+
+    public void DrawLine(int x0, int y0, int x1, int y1, (byte R, byte G, byte B) color)
+    {
+        int dx = Math.Abs(x1 - x0), sx = x0 < x1 ? 1 : -1;
+        int dy = -Math.Abs(y1 - y0), sy = y0 < y1 ? 1 : -1;
+        int error = dx + dy;
+
+        while (true)
+        {
+            SetPixel(x0, y0, color);
+            if (x0 == x1 && y0 == y1)
+            {
+                return;
+            }
+
+            int doubled = 2 * error;
+            if (doubled >= dy)
+            {
+                error += dy;
+                x0 += sx;
+            }
+
+            if (doubled <= dx)
+            {
+                error += dx;
+                y0 += sy;
+            }
+        }
+    }
+
+    // Text is drawn in upper case; characters without a glyph are left blank
+    public void DrawText(int x, int y, string text, (byte R, byte G, byte B) color, int scale = 1)
+    {
+        foreach (char character in text.ToUpperInvariant())
+        {
+            if (Font.TryGetValue(character, out string[]? glyph))
+            {
+                for (int row = 0; row < glyph.Length; row++)
+                {
+                    for (int column = 0; column < glyph[row].Length; column++)
+                    {
+                        if (glyph[row][column] == '1')
+                        {
+                            FillRect(x + column * scale, y + row * scale, scale, scale, color);
+                        }
+                    }
+                }
+            }
+
+            x += GlyphWidth * scale;
+        }
+    }
+
+    public async Task SaveAsync(string path)
+    {
+        await using FileStream file = File.Create(path);
+        file.Write(new byte[] { 0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A });
+
+        byte[] header = new byte[13];
+        BinaryPrimitives.WriteInt32BigEndian(header, Width);
+        BinaryPrimitives.WriteInt32BigEndian(header.AsSpan(4), Height);
+        header[8] = 8; // bit depth
+        header[9] = 2; // truecolor RGB
+        WriteChunk(file, "IHDR", header);
+
+        using MemoryStream compressed = new MemoryStream();
+        using (ZLibStream zlib = new ZLibStream(compressed, CompressionLevel.Optimal, leaveOpen: true))
+        {
+            for (int row = 0; row < Height; row++)
+            {
+                zlib.WriteByte(0); // no scanline filter
+                zlib.Write(_pixels, row * Width * 3, Width * 3);
+            }
+        }
+
+        WriteChunk(file, "IDAT", compressed.ToArray());
+        WriteChunk(file, "IEND", Array.Empty<byte>());
+    }
+
+    private static void WriteChunk(Stream stream, string type, byte[] data)
+    {
+        byte[] length = new byte[4];
+        BinaryPrimitives.WriteInt32BigEndian(length, data.Length);
+        stream.Write(length);
+
+        byte[] typeBytes = Encoding.ASCII.GetBytes(type);
+        stream.Write(typeBytes);
+        stream.Write(data);
+
+        uint crc = 0xFFFFFFFF;
+        foreach (byte b in typeBytes.Concat(data))
+        {
+            crc = CrcTable[(crc ^ b) & 0xFF] ^ (crc >> 8);
+        }
+
+        byte[] crcBytes = new byte[4];
+        BinaryPrimitives.WriteUInt32BigEndian(crcBytes, crc ^ 0xFFFFFFFF);
+        stream.Write(crcBytes);
+    }
+
+    private static uint[] BuildCrcTable()
+    {
+        uint[] table = new uint[256];
+
+        for (uint n = 0; n < table.Length; n++)
+        {
+            uint c = n;
+            for (int k = 0; k < 8; k++)
+            {
+                c = (c & 1) != 0 ? 0xEDB88320 ^ (c >> 1) : c >> 1;
+            }
+
+            table[n] = c;
+        }
+
+        return table;
+    }
+}
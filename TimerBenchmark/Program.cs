@@ -1,14 +1,46 @@
-﻿using System.Diagnostics;
+﻿using System.ComponentModel;
+using System.Diagnostics;
 using System.Diagnostics.CodeAnalysis;
 using System.Security.Principal;
+using System.Text;
+using System.Text.Json;
 using Microsoft.Extensions.Configuration;
 
 namespace TimerBenchmark;
 
 internal abstract class TimerBenchmark
 {
+    // A Sleep(1) delta this many times the requested resolution means something else is controlling the timer
+    private const double SuspiciousDeltaFactor = 10;
+
+    // Waits used to judge how noisy the idle system is before committing to a sweep
+    private const int NoiseCheckSamples = 100;
+
+    private const string MergedResultsPath = "golden.csv";
+
+    // Failing this many resolutions before any succeeds means the measurement itself is broken, not the timer
+    private const int NothingMeasuredAfter = 3;
+
+    // How long KillProcess waits for a killed helper to exit before moving on
+    private const int KillTimeoutMs = 2000;
+
+    // Below this spread between the best and worst delta the choice of resolution hardly matters
+    private const double InsensitiveSpreadMs = 0.05;
+
+    // Commonly recommended resolutions benchmarked head-to-head by --standard-set
+    private static readonly double[] StandardResolutions = { 0.5, 0.5004, 0.5024, 0.5064, 0.5096, 0.512, 0.52, 0.9766, 1 };
+
     private static bool? _isAdmin;
 
+    // Started first thing in Main, so the --json-summary of a failed run can report how long it took
+    private static readonly Stopwatch RunTime = new Stopwatch();
+
+    // The real stdout, kept for JSON output after --stream-jsonl moves the human-readable output to stderr
+    private static TextWriter _jsonOut = TextWriter.Null;
+
+    // The resolution this process last requested with --native-set, released once the sweep is done
+    private static uint? _nativeRequest;
+
     private static bool IsAdmin()
     {
         if (_isAdmin == null)
@@ -21,131 +53,1436 @@ internal abstract class TimerBenchmark
 
     [RequiresDynamicCode("Calls Microsoft.Extensions.Configuration.ConfigurationBinder.Get<T>()")]
     [RequiresUnreferencedCode("Calls Microsoft.Extensions.Configuration.ConfigurationBinder.Get<T>()")]
-    private static async Task Main()
+    private static async Task Main(string[] args)
     {
-        IConfiguration config = new ConfigurationBuilder()
-            .AddJsonFile("appsettings.json", optional: false, reloadOnChange: true)
+        RunTime.Start();
+
+        // Must run before anything is written so Console.Out picks up the UTF-8 code page
+        ConsoleCodePage.UseUtf8();
+        _jsonOut = Console.Out;
+
+        string configPath = CommandLineOptions.ConfigPathFrom(args);
+
+        // Results, helpers and state files are all relative to the working directory, so keep them next to a
+        // configuration that was only found beside the exe
+        string configDirectory = Path.GetDirectoryName(configPath)!;
+        bool explicitConfig = CommandLineOptions.ExplicitConfigPath(args) is not null;
+        if (!explicitConfig && !PathsEqual(configDirectory, Environment.CurrentDirectory))
+        {
+            Directory.SetCurrentDirectory(configDirectory);
+            Console.WriteLine($"info: {CommandLineOptions.DefaultConfigPath} not found in the working directory, using {configDirectory}");
+        }
+
+        // The wizard creates the configuration, so it has to run before the configuration is required to exist
+        if (args.Contains("--config-wizard"))
+        {
+            await ConfigWizard.RunAsync(configPath);
+            return;
+        }
+
+        if (explicitConfig && !File.Exists(configPath))
+        {
+            await Console.Error.WriteLineAsync($"error: configuration file {configPath} does not exist");
+            Environment.Exit(1);
+            return;
+        }
+
+        // Optional so a headless run can take every parameter from the command line; a missing file is reported below
+        // only when something is left unset
+        ConfigurationBuilder configBuilder = new ConfigurationBuilder();
+        IConfiguration config = (Path.GetExtension(configPath).ToLowerInvariant() is ".yaml" or ".yml"
+                ? configBuilder.AddYamlFile(configPath, optional: true, reloadOnChange: true)
+                : configBuilder.AddJsonFile(configPath, optional: true, reloadOnChange: true))
             .Build();
 
-        BenchmarkingParameters? parameters = config.GetSection("BenchmarkingParameters").Get<BenchmarkingParameters>();
+        CommandLineOptions options;
+        try
+        {
+            CommandLineOptions? defaults = config.GetSection("Options")
+                .Get<CommandLineOptions>(binder => binder.BindNonPublicProperties = true);
+            options = CommandLineOptions.Parse(args, defaults);
+        }
+        catch (Exception e) when (e is ArgumentException or InvalidOperationException)
+        {
+            await Console.Error.WriteLineAsync($"error: {e.Message}");
+            Environment.Exit(1);
+            return;
+        }
+
+        Explain.Enabled = options.Explain;
+        PhaseTiming.Enabled = options.Timing;
+        Glyphs.Ascii = options.Ascii || !Glyphs.ConsoleSupportsUnicode;
+        NumberFormat.Digits = options.Precision;
+
+        // Streaming to stdout leaves it to the JSON lines, so it can be piped straight into a JSON consumer
+        if (options.JsonToStdout)
+        {
+            Console.SetOut(Console.Error);
+        }
+
+        if (options.ListTimerInfo)
+        {
+            await TimerInfo.PrintAsync();
+            return;
+        }
+
+        if (options.Command == Command.Set)
+        {
+            await SetResolutionAsync(options);
+            return;
+        }
+
+        if (options.Command == Command.Merge)
+        {
+            await MergeResultsAsync(options);
+            return;
+        }
+
+        if (options.Command == Command.Doctor)
+        {
+            string[] helpers = new[] { "SetTimerResolution.exe", "MeasureSleep.exe" };
+            bool healthy = await Doctor.RunAsync(options, HelperDirectory(options, helpers), IsAdmin());
+            Environment.ExitCode = healthy ? 0 : 1;
+            return;
+        }
+
+        if (options.RawSamplesPath is { } rawSamplesPath)
+        {
+            await ImportRawSamplesAsync(options, rawSamplesPath);
+            return;
+        }
+
+        IConfigurationSection parametersSection = config.GetSection("BenchmarkingParameters");
+        BenchmarkingParameters? parameters = parametersSection.Get<BenchmarkingParameters>();
+
+        // A ResolutionList replaces the range; an empty list binds as no list at all, so it ends up in the second check.
+        // Passing the whole range on the command line replaces the file, list included
+        bool cliReplaces = options is { StartValue: not null, IncrementValue: not null, EndValue: not null, SampleValue: not null };
+        bool rangeGiven = options.StartValue is not null || options.IncrementValue is not null || options.EndValue is not null
+                          || new[] { "StartValue", "IncrementValue", "EndValue" }
+                              .Any(key => parametersSection.GetSection(key).Exists());
+        if (!cliReplaces && parameters?.ResolutionList is { } resolutionList)
+        {
+            if (rangeGiven)
+            {
+                Fail(options, "invalid_configuration", configPath,
+                    "ResolutionList cannot be combined with StartValue, IncrementValue and EndValue");
+            }
+
+            if (ResolutionSweep.ListError(resolutionList) is { } listError)
+            {
+                Fail(options, "invalid_configuration", configPath, listError);
+            }
+        }
+        else if (!cliReplaces && parameters is not null && !rangeGiven)
+        {
+            Fail(options, "invalid_configuration", configPath,
+                "BenchmarkingParameters needs a non-empty ResolutionList or StartValue, IncrementValue and EndValue");
+        }
+
+        // --start, --increment, --end and --samples override the file one by one, or replace it when all are given
+        if (options is { StartValue: { } start, IncrementValue: { } increment, EndValue: { } end, SampleValue: { } samples })
+        {
+            parameters = new BenchmarkingParameters
+            {
+                StartValue = start,
+                IncrementValue = increment,
+                EndValue = end,
+                SampleValue = samples
+            };
+        }
+        else if (parameters is not null)
+        {
+            parameters = new BenchmarkingParameters
+            {
+                StartValue = options.StartValue ?? parameters.StartValue,
+                IncrementValue = options.IncrementValue ?? parameters.IncrementValue,
+                EndValue = options.EndValue ?? parameters.EndValue,
+                SampleValue = options.SampleValue ?? parameters.SampleValue,
+                ResolutionList = parameters.ResolutionList
+            };
+        }
 
         if (parameters is null)
         {
-            Console.WriteLine("Error: Unable to read configuration parameters.");
+            Fail(options, "invalid_configuration", configPath, File.Exists(configPath)
+                ? "unable to read configuration parameters"
+                : $"{configPath} not found, create it with --config-wizard or pass --start, --increment, --end and --samples");
+            return;
+        }
+
+        // Checked once the command line is merged in, since e.g. --end alone can move the end below the file's start
+        if (parameters.ResolutionList is null && ResolutionSweep.RangeError(parameters) is { } rangeError)
+        {
+            Fail(options, "invalid_configuration", configPath, rangeError);
+        }
+
+        if (options.DumpConfig)
+        {
+            Console.WriteLine(EffectiveConfigJson(parameters, options));
+            return;
+        }
+
+        if (options.Watch is { } watchInterval && !Watch.IsChild)
+        {
+            await Watch.RunAsync(args, watchInterval, options);
             return;
         }
 
         if (!IsAdmin())
         {
-            await Console.Error.WriteLineAsync("error: administrator privileges required");
-            Environment.Exit(1);
+            // An administrator account still runs unelevated under UAC unless explicitly elevated
+            string hint = SystemInfo.IsUacEnabled
+                ? "UAC is enabled, so shells started by an administrator account are not elevated by default." +
+                  $"{Environment.NewLine}Right-click TimerBenchmark.exe and choose 'Run as administrator', or run:" +
+                  $"{Environment.NewLine}    Start-Process -Verb RunAs -FilePath \"{Environment.ProcessPath}\""
+                : "UAC is disabled, so sign in with an account that is a member of the Administrators group.";
+            Fail(options, "not_admin", SystemInfo.IsUacEnabled ? "uac_enabled" : "uac_disabled",
+                $"administrator privileges required{Environment.NewLine}{hint}");
+        }
+
+        if (options.AdaptiveIncrement is not null && parameters.ResolutionList is not null)
+        {
+            Fail(options, "invalid_configuration", configPath, "'--adaptive' needs a range, not a ResolutionList");
+        }
+
+        string? sweepWarning = null;
+        IReadOnlyList<double> requestedValues = options.StandardSet
+            ? StandardResolutions
+            : parameters.ResolutionList ?? (IReadOnlyList<double>)ResolutionSweep.Values(parameters, out sweepWarning);
+        if (sweepWarning is not null)
+        {
+            Console.WriteLine($"warning: {sweepWarning}");
+        }
+
+        List<BenchmarkResult>? previousResults = null;
+        if (options.RefineNoisy is { } noisyStdev)
+        {
+            if (!File.Exists(options.ResultsPath))
+            {
+                Fail(options, "missing_results", options.ResultsPath,
+                    $"--refine-noisy needs the {options.ResultsPath} of a previous run");
+            }
+
+            previousResults = BenchmarkReport.ParseCsv(await File.ReadAllTextAsync(options.ResultsPath));
+            requestedValues = previousResults.Where(r => r.Stdev > noisyStdev).Select(r => r.ResolutionMs).ToList();
+            parameters = new BenchmarkingParameters
+            {
+                StartValue = parameters.StartValue,
+                IncrementValue = parameters.IncrementValue,
+                EndValue = parameters.EndValue,
+                SampleValue = options.RefineSamples ?? parameters.SampleValue * 2,
+                ResolutionList = parameters.ResolutionList
+            };
+            Console.WriteLine(
+                $"info: re-measuring {requestedValues.Count} of {previousResults.Count} resolutions with a STDEV above {noisyStdev}");
+        }
+
+        // Windows snaps requests outside its supported range to the nearest limit, which only produces duplicate rows
+        if (TimerResolution.Query() is { } supported)
+        {
+            bool Supported(double value) => value >= supported.FinestMs && value <= supported.CoarsestMs;
+            int unsupported = requestedValues.Count(value => !Supported(value));
+
+            if (unsupported > 0 && options.NoClamp)
+            {
+                Console.WriteLine(
+                    $"warning: {unsupported} of {requestedValues.Count} resolutions are outside the supported range of {supported.FinestMs}ms to {supported.CoarsestMs}ms and will be snapped to it by Windows");
+            }
+            else if (unsupported > 0)
+            {
+                Console.WriteLine(
+                    $"warning: skipping {unsupported} of {requestedValues.Count} resolutions outside the supported range of {supported.FinestMs}ms to {supported.CoarsestMs}ms, pass --no-clamp to request them anyway");
+                requestedValues = requestedValues.Where(Supported).ToList();
+
+                if (requestedValues.Count == 0)
+                {
+                    Fail(options, "no_supported_resolutions", $"{supported.FinestMs}-{supported.CoarsestMs}",
+                        "none of the requested resolutions are supported by this system");
+                }
+
+                if (!options.StandardSet && parameters.ResolutionList is null && previousResults is null)
+                {
+                    parameters = new BenchmarkingParameters
+                    {
+                        StartValue = requestedValues[0],
+                        IncrementValue = parameters.IncrementValue,
+                        EndValue = requestedValues[^1],
+                        SampleValue = parameters.SampleValue
+                    };
+                }
+            }
         }
 
-        decimal iterations =
-            (decimal)(parameters.EndValue - parameters.StartValue) / (decimal)parameters.IncrementValue;
-        decimal totalMinutes = iterations * parameters.SampleValue * 2 / 60000m; // Assuming Sleep(1) = ~2ms
+        int sweepIterations = requestedValues.Count * options.Passes;
+        if (options.AdaptiveIncrement is { } finest)
+        {
+            sweepIterations += ResolutionSweep.AdaptiveProbeCount(parameters.IncrementValue, finest);
+        }
+
+        decimal iterations = sweepIterations + options.WarmupIterations;
+        decimal totalMinutes = options.TimePerResolution is { } timePerResolution
+            ? iterations * (decimal)timePerResolution.TotalMinutes
+            : iterations * parameters.SampleValue * 2 / 60000m; // Assuming Sleep(1) = ~2ms
+        totalMinutes += Math.Max(sweepIterations - 1, 0) * options.CooldownMs / 60000m;
 
         Console.WriteLine($"Approximate worst-case estimated time for completion: {Math.Round(totalMinutes, 2)} mins");
         Console.WriteLine("Worst-case is determined by assuming Sleep(1) = ~2ms with 1ms Timer Resolution");
-        Console.WriteLine(
-            $"Start: {parameters.StartValue}, End: {parameters.EndValue}, Increment: {parameters.IncrementValue}, Samples: {parameters.SampleValue}");
+        Console.WriteLine(options.StandardSet || parameters.ResolutionList is not null || previousResults is not null
+            ? $"Resolutions: {string.Join(", ", requestedValues)}, Samples: {parameters.SampleValue}"
+            : $"Start: {parameters.StartValue}, End: {parameters.EndValue}, Increment: {parameters.IncrementValue}, Samples: {parameters.SampleValue}");
+        ClockSource.Report();
+
+        if (SystemInfo.HypervisorPresent)
+        {
+            Console.WriteLine(
+                $"info: Windows is running under a hypervisor (VBS: {(SystemInfo.VbsEnabled ? "on" : "off")}, HVCI: {(SystemInfo.HvciEnabled ? "on" : "off")}), which virtualizes timer interrupts and can add latency to every resolution");
+        }
+
+        (bool globalResolution, string resolutionScope) = SystemInfo.TimerResolutionScope;
+        Console.WriteLine(globalResolution
+            ? $"info: timer resolution requests are global: {resolutionScope}"
+            : $"warning: timer resolution requests are per-process: {resolutionScope}, so the resolution set by SetTimerResolution may not apply to MeasureSleep");
+
+        if (CpuFrequency.Sample() is { } startFrequency)
+        {
+            Console.WriteLine(
+                $"info: CPU frequency: {startFrequency.CurrentMhz}/{startFrequency.MaxMhz} MHz, deepest idle state: C{startFrequency.MaxIdleState}");
+            if (startFrequency.MaxIdleState > 1)
+            {
+                Console.WriteLine("info: deep C-states add wake-up latency, consider an idle disabled power plan");
+            }
+        }
+
+        BootConfiguration? bootConfiguration = null;
+        try
+        {
+            bootConfiguration = await BootConfiguration.QueryAsync();
+            Explain.Write("platform clock and dynamic tick are read from the useplatformclock and disabledynamictick entries of 'bcdedit /enum {current}'");
+            bootConfiguration.Report();
+        }
+        catch (Exception e) when (e is Win32Exception or InvalidOperationException)
+        {
+            // The HPET check is advisory, measuring does not depend on it
+            Console.WriteLine($"warning: Platform Clock (HPET) and Dynamic Tick: indeterminate ({e.Message})");
+        }
+
+        using (Process current = Process.GetCurrentProcess())
+        {
+            // MeasureSleep inherits the affinity; its priority class is set when it starts
+            if (options.Priority is { } priority)
+            {
+                current.PriorityClass = priority;
+            }
+
+            if (options.AffinityCore is { } core)
+            {
+                current.ProcessorAffinity = (IntPtr)(1L << core);
+            }
+        }
+
+        if (options.NoiseCheckStdev is { } maxNoise)
+        {
+            // A high resolution timer is not bound to the global resolution, so its jitter reflects the system alone
+            Measurement noise = await Task.Run(() =>
+                NativeMeasurer.MeasureHighResolution(1, NoiseCheckSamples, options.DiscardSamples));
+            Console.WriteLine($"info: idle jitter is {NumberFormat.Round(noise.Stdev)}ms STDEV over {NoiseCheckSamples} waits");
+
+            if (noise.Stdev > maxNoise)
+            {
+                Fail(options, "too_noisy", $"{noise.Stdev}",
+                    $"the system is too noisy to benchmark (STDEV {NumberFormat.Round(noise.Stdev)}ms > {maxNoise}ms){Environment.NewLine}close background applications, plug in laptops and try again");
+            }
+        }
+
+        bool loadProcessRunning = false;
+        if (options.LoadProcess is { } loadProcess)
+        {
+            loadProcessRunning = IsProcessRunning(loadProcess);
+            Console.WriteLine(loadProcessRunning
+                ? $"info: {loadProcess} is running, results reflect the system under its load"
+                : $"warning: {loadProcess} is not running, results reflect an idle system");
+        }
+
+        using Process? loadGenerator = options.LoadCommand is { } loadCommand
+            ? Process.Start(new ProcessStartInfo(loadCommand) { UseShellExecute = true })
+            : null;
+        if (loadGenerator is not null)
+        {
+            Console.WriteLine($"info: started load generator {options.LoadCommand}");
+        }
+
+        // Started once the load generator exists so it is killed too; the time spent before this counts against it
+        using Timer? watchdog = options.GlobalTimeout is { } globalTimeout
+            ? StartGlobalTimeout(options, globalTimeout - RunTime.Elapsed, loadGenerator)
+            : null;
 
         KillProcess("SetTimerResolution");
-        string currentDirectory = Environment.CurrentDirectory;
 
-        string[] dependencies = new[] { "SetTimerResolution.exe", "MeasureSleep.exe" };
+        // Taken once a leftover SetTimerResolution is gone, so it reflects what the rest of the system requests
+        TimerResolution? resolutionBefore = TimerResolution.Query();
+
+        string[] dependencies = UsesMeasureSleep(options)
+            ? new[] { "SetTimerResolution.exe", "MeasureSleep.exe" }
+            : new[] { "SetTimerResolution.exe" };
+        string helperDirectory = HelperDirectory(options, dependencies);
 
-        bool hasMissingDependencies = false;
+        string? missingDependency = null;
         object missingDependenciesLock = new object();
 
         Parallel.ForEach(dependencies, dependency =>
         {
-            string fullPath = Path.Combine(currentDirectory, dependency);
+            string fullPath = Path.Combine(helperDirectory, dependency);
             if (!File.Exists(fullPath))
             {
                 lock (missingDependenciesLock)
                 {
-                    if (!hasMissingDependencies)
-                    {
-                        Console.WriteLine($"Error: {dependency} does not exist in the current directory");
-                        hasMissingDependencies = true;
-                    }
+                    missingDependency ??= dependency;
                 }
             }
         });
 
-        if (hasMissingDependencies)
+        if (missingDependency is not null)
         {
-            return;
+            Fail(options, "missing_dependency", missingDependency,
+                $"{missingDependency} does not exist in {helperDirectory}");
         }
 
-        string content = "RequestedResolutionMs,DeltaMs,STDEV";
-        await File.WriteAllTextAsync("results.txt", content);
+        await using JsonLinesStream? jsonLines =
+            options.StreamJsonLines ? JsonLinesStream.Open(options.StreamJsonLinesPath, _jsonOut) : null;
+
+        LiveChart? liveChart = null;
+        if (options.Tui)
+        {
+            if (LiveChart.IsSupported)
+            {
+                liveChart = new LiveChart(options.OptimalBy, sweepIterations, options.AdaptiveIncrement is not null);
+            }
+            else
+            {
+                Console.WriteLine("info: console output is redirected, falling back to plain progress output");
+            }
+        }
 
-        for (double i = parameters.StartValue; i <= parameters.EndValue; i += parameters.IncrementValue)
+        SortedDictionary<byte, ulong>? coreMasks = null;
+        if (options.PerCoreType)
         {
-            double formattedValue = Math.Round(i, 4, MidpointRounding.AwayFromZero);
-            Console.WriteLine($"info: benchmarking {formattedValue}");
+            try
+            {
+                coreMasks = CoreTopology.EfficiencyClassMasks();
+                if (coreMasks.Count < 2)
+                {
+                    Console.WriteLine("warning: all cores share one efficiency class, skipping per-core-type measurements");
+                    coreMasks = null;
+                }
+            }
+            catch (Win32Exception e)
+            {
+                Console.WriteLine($"warning: unable to query core types, skipping per-core-type measurements: {e.Message}");
+            }
+        }
+
+        Dictionary<byte, List<BenchmarkResult>> coreResults = new Dictionary<byte, List<BenchmarkResult>>();
+        List<BenchmarkResult> results = new List<BenchmarkResult>();
+        bool aborted = false;
 
-            int resolution = (int)(formattedValue * 1E4);
-            await Task.Run(() =>
+        // The first Ctrl+C stops the sweep and discards the resolution being measured, so the partial results are
+        // saved and the timer requests withdrawn below; a second one exits at once, still without leaving
+        // SetTimerResolution behind
+        using CancellationTokenSource interrupted = new CancellationTokenSource();
+        ConsoleCancelEventHandler onCancel = (_, e) =>
+        {
+            if (interrupted.IsCancellationRequested)
             {
-                Process.Start(Path.Combine(currentDirectory, "SetTimerResolution.exe"),
-                    $"--resolution {resolution} --no-console");
-            });
+                KillProcess("SetTimerResolution");
+                return;
+            }
+
+            e.Cancel = true;
+            interrupted.Cancel();
+            Console.WriteLine("info: stopping once the current resolution ends, its row is discarded, press Ctrl+C again to exit immediately");
+        };
+        Console.CancelKeyPress += onCancel;
 
-            // Delay after setting resolution
-            await Task.Delay(1);
+        // Full set/measure cycles at the start value let caches and the scheduler settle before the first recorded row
+        for (int warmup = 1; warmup <= options.WarmupIterations && !interrupted.IsCancellationRequested; warmup++)
+        {
+            int resolution = ResolutionSweep.ToUnits(
+                requestedValues.Count > 0 ? requestedValues[0] : parameters.StartValue, options.Rounding);
+            Console.WriteLine($"info: warm-up iteration {warmup}/{options.WarmupIterations} at {resolution / 1E4}ms");
 
-            ProcessStartInfo startInfo = new ProcessStartInfo
+            try
             {
-                FileName = Path.Combine(currentDirectory, "MeasureSleep.exe"),
-                Arguments = $"--samples {parameters.SampleValue}",
-                UseShellExecute = false,
-                RedirectStandardOutput = true
-            };
+                using ResolutionHolder? holder = await ApplyResolutionAsync(helperDirectory, resolution, options);
+                await Task.Delay(1);
+                await MeasureAsync(helperDirectory, parameters, options);
+            }
+            catch (Exception e) when (e is Win32Exception or InvalidOperationException)
+            {
+                Console.WriteLine($"warning: warm-up iteration {warmup} failed: {e.Message}");
+            }
+            finally
+            {
+                if (!options.NativeSet && !options.HoldInThread)
+                {
+                    KillProcess("SetTimerResolution");
+                }
+            }
+        }
+
+        if (options.DryMeasure)
+        {
+            Console.WriteLine(
+                "info: --dry-measure leaves the timer resolution untouched, every row measures the current resolution so the spread is the harness's own noise");
+        }
+
+        bool firstIteration = true;
+        int failuresBeforeFirstResult = 0;
+        int skipped = 0;
+        string? lastFailure = null;
+        IEnumerable<double> sweepValues = options.AdaptiveIncrement is { } finestIncrement
+            ? ResolutionSweep.AdaptiveValues(requestedValues, parameters, finestIncrement, results, options.Rounding,
+                options.OptimalBy)
+            : requestedValues;
+        IEnumerable<(double Value, int Pass, bool FirstOfPass)> sweep = Enumerable.Range(1, options.Passes)
+            .SelectMany(pass => sweepValues.Select((value, index) => (value, pass, index == 0)));
+        foreach ((double i, int pass, bool firstOfPass) in sweep)
+        {
+            if (interrupted.IsCancellationRequested)
+            {
+                aborted = true;
+                break;
+            }
+
+            if (options.Passes > 1 && firstOfPass)
+            {
+                Console.WriteLine($"info: pass {pass}/{options.Passes}");
+            }
+
+            // Lets thermally or power constrained systems recover before the next resolution is applied
+            if (!firstIteration && options.CooldownMs > 0)
+            {
+                await Task.Delay(options.CooldownMs);
+            }
+
+            firstIteration = false;
+            PhaseTiming.BeginIteration();
+            int resolution = ResolutionSweep.ToUnits(i, options.Rounding);
+            double formattedValue = resolution / 1E4;
+            if (liveChart is null)
+            {
+                Console.WriteLine($"info: benchmarking {i}ms (effective: {formattedValue}ms)");
+            }
 
-            Process? process = Process.Start(startInfo);
-            string output = await process?.StandardOutput.ReadToEndAsync()!;
-            await process.WaitForExitAsync();
+            Explain.Write(
+                $"{i}ms is rounded ({options.Rounding.ToString().ToLowerInvariant()}) to {resolution} units of 100ns ({formattedValue}ms) for SetTimerResolution");
 
-            string[] outputLines = output.Split(new[] { Environment.NewLine }, StringSplitOptions.RemoveEmptyEntries);
-            (double avg, double stdev) = (0, 0);
+            Measurement? measurement = null;
+            List<(byte EfficiencyClass, BenchmarkResult Result)> pinnedResults =
+                new List<(byte EfficiencyClass, BenchmarkResult Result)>();
+            double? highResTimerDelta = null;
+            Measurement? tickJitter = null;
+            bool throttled = false;
+            DateTimeOffset? measuredAt = null;
+            string? failure = null;
 
-            foreach (var line in outputLines)
+            try
             {
-                if (line.StartsWith("Avg: ") && double.TryParse(line.AsSpan(5), out var parsedAvg))
+                long setStart = Stopwatch.GetTimestamp();
+                using ResolutionHolder? holder = await ApplyResolutionAsync(helperDirectory, resolution, options);
+                PhaseTiming.Record("set", setStart);
+
+                // Delay after setting resolution
+                long settleStart = Stopwatch.GetTimestamp();
+                await Task.Delay(1);
+                PhaseTiming.Record("settle", settleStart);
+
+                measuredAt = options.Timestamps ? DateTimeOffset.Now : null;
+                measurement = await MeasureAsync(helperDirectory, parameters, options);
+
+                CpuFrequency? frequency = CpuFrequency.Sample();
+                if (frequency is { IsThrottled: true })
+                {
+                    throttled = true;
+                    Console.WriteLine(
+                        $"warning: CPU is throttled (limited to {frequency.Value.LimitMhz}/{frequency.Value.MaxMhz} MHz), the row at {formattedValue}ms is tagged as throttled");
+                }
+                else if (frequency is { IsDownclocked: true })
+                {
+                    Console.WriteLine(
+                        $"warning: CPU appears to be downclocking ({frequency.Value.CurrentMhz}/{frequency.Value.MaxMhz} MHz), power saving may inflate the delta at {formattedValue}ms");
+                }
+
+                if (!measurement.IsValid)
+                {
+                    List<Measurement> retried = new List<Measurement>();
+                    for (int retry = 0; retry < options.Retries && !interrupted.IsCancellationRequested; retry++)
+                    {
+                        // A SetTimerResolution that crashed or was killed took its request with it
+                        if (!options.NativeSet && !options.HoldInThread && !options.DryMeasure
+                            && !IsProcessRunning("SetTimerResolution"))
+                        {
+                            await ApplyResolutionAsync(helperDirectory, resolution, options);
+                            await Task.Delay(1);
+                        }
+
+                        Measurement attempt = await MeasureAsync(helperDirectory, parameters, options);
+
+                        if (attempt.IsValid)
+                        {
+                            retried.Add(attempt);
+                            if (options.RetryAggregation == RetryAggregation.First)
+                            {
+                                break;
+                            }
+                        }
+                    }
+
+                    Explain.Write(
+                        $"measurement was invalid, {retried.Count} of {options.Retries} retries succeeded, combined with '{options.RetryAggregation.ToString().ToLowerInvariant()}'");
+
+                    if (retried.Count > 0)
+                    {
+                        measurement = Measurement.Aggregate(retried, options.RetryAggregation);
+                    }
+                }
+
+                if (!measurement.IsValid)
+                {
+                    failure = "measurement output is invalid";
+                }
+                else if (coreMasks is not null)
+                {
+                    foreach ((byte efficiencyClass, ulong mask) in coreMasks)
+                    {
+                        Measurement pinned;
+                        try
+                        {
+                            pinned = await Task.Run(() =>
+                                NativeMeasurer.MeasurePinned(mask, 1, parameters.SampleValue, options.DiscardSamples));
+                        }
+                        catch (Exception e) when (e is Win32Exception or InvalidOperationException)
+                        {
+                            // e.g. --affinity keeps the process off this class's cores; the row itself is still good
+                            Console.WriteLine(
+                                $"warning: efficiency class {efficiencyClass} could not be measured at {formattedValue}ms: {e.Message}");
+                            continue;
+                        }
+
+                        Console.WriteLine(
+                            $"info:   efficiency class {efficiencyClass}: delta {NumberFormat.Round(pinned.Avg)}ms, STDEV {NumberFormat.Round(pinned.Stdev)}");
+
+                        pinnedResults.Add((efficiencyClass,
+                            new BenchmarkResult(formattedValue, resolution, pinned.Avg, pinned.Stdev)));
+                    }
+                }
+
+                if (failure is null && options.HighResTimer)
                 {
-                    avg = parsedAvg;
+                    Measurement highRes = await Task.Run(() =>
+                        NativeMeasurer.MeasureHighResolution(1, parameters.SampleValue, options.DiscardSamples));
+                    highResTimerDelta = highRes.Avg;
                 }
-                else if (line.StartsWith("STDEV: ") && double.TryParse(line.AsSpan(7), out var parsedStdev))
+
+                if (failure is null && options.MeasureTickJitter)
+                {
+                    tickJitter = await Task.Run(() => TickJitter.Measure(parameters.SampleValue));
+                }
+            }
+            catch (Exception e) when (e is Win32Exception or InvalidOperationException)
+            {
+                failure = e.Message;
+            }
+            finally
+            {
+                if (!options.NativeSet && !options.HoldInThread)
                 {
-                    stdev = parsedStdev;
+                    long killStart = Stopwatch.GetTimestamp();
+                    KillProcess("SetTimerResolution");
+                    PhaseTiming.Record("kill", killStart);
                 }
             }
 
-            string resultLine = $"{formattedValue}, {Math.Round(avg, 4)}, {stdev}{Environment.NewLine}";
-            await File.AppendAllTextAsync("results.txt", resultLine);
+            PhaseTiming.EndIteration(formattedValue);
 
-            KillProcess("SetTimerResolution");
-        }
+            // Ctrl+C reaches MeasureSleep too, and the helper may already be gone, so nothing measured while it was
+            // pressed can be trusted
+            if (interrupted.IsCancellationRequested)
+            {
+                aborted = true;
+                break;
+            }
 
-        Console.WriteLine("info: results saved in results.txt");
-    }
+            if (failure is not null || measurement is null)
+            {
+                await Console.Error.WriteLineAsync($"error: {failure} at {formattedValue}ms");
+                lastFailure = failure;
 
-    private class BenchmarkingParameters
-    {
-        public double StartValue { get; init; }
-        public double IncrementValue { get; init; }
-        public double EndValue { get; init; }
-        public int SampleValue { get; init; }
+                // No point sweeping on when nothing can be measured at all
+                if (results.Count == 0 && ++failuresBeforeFirstResult >= NothingMeasuredAfter)
+                {
+                    FailNothingMeasured(options, helperDirectory, failure);
+                }
+
+                Explain.Write(options.OnError == ErrorPolicy.Abort
+                    ? "stopping the sweep because --on-error is 'abort'"
+                    : "skipping this resolution because --on-error is 'continue'");
+
+                if (options.OnError == ErrorPolicy.Abort)
+                {
+                    aborted = true;
+                    break;
+                }
+
+                skipped++;
+                continue;
+            }
+
+            foreach ((byte efficiencyClass, BenchmarkResult pinnedResult) in pinnedResults)
+            {
+                if (!coreResults.TryGetValue(efficiencyClass, out List<BenchmarkResult>? classResults))
+                {
+                    classResults = coreResults[efficiencyClass] = new List<BenchmarkResult>();
+                }
+
+                classResults.Add(pinnedResult);
+            }
+
+            // MeasureSleep without per-sample lines only reports its statistics, which cover the samples asked for
+            int sampleCount = measurement.Samples.Count > 0 ? measurement.Samples.Count : parameters.SampleValue;
+            BenchmarkResult result =
+                new BenchmarkResult(formattedValue, resolution, measurement.Avg, measurement.Stdev, highResTimerDelta,
+                    tickJitter?.Avg, tickJitter?.Stdev, throttled, measuredAt, measurement.Median, measurement.Min,
+                    measurement.Max, sampleCount);
+            results.Add(result);
+            liveChart?.Update(result, i);
+
+            if (jsonLines is not null)
+            {
+                await jsonLines.WriteAsync(result.ResolutionMs, result.DeltaMs, result.Stdev);
+            }
+        }
+
+        Console.CancelKeyPress -= onCancel;
+
+        // Refinement levels are probed after the coarse sweep, so their rows are put back in resolution order
+        if (options.AdaptiveIncrement is not null)
+        {
+            results = results.OrderBy(r => r.ResolutionMs).ToList();
+        }
+
+        // Each resolution now has a row per pass; they are combined the same way 'merge' combines runs
+        if (options.Passes > 1 && results.Count > 0)
+        {
+            results = BenchmarkReport.Merge(new[] { results }).Results.ToList();
+            Explain.Write($"each delta is the mean of {options.Passes} passes and each STDEV is pooled across them");
+        }
+
+        if (loadGenerator is { HasExited: false })
+        {
+            loadGenerator.Kill(entireProcessTree: true);
+        }
+
+        PhaseTiming.Report();
+
+        // Windows goes back to the finest resolution any other process requests once ours are withdrawn, so releasing
+        // them is what restores the resolution the system had before the run
+        KillProcess("SetTimerResolution");
+        if (_nativeRequest is { } nativeRequest)
+        {
+            TimerResolution.Release(nativeRequest);
+            _nativeRequest = null;
+        }
+
+        if (resolutionBefore is { } before && TimerResolution.Query() is { } after)
+        {
+            ReportResolutionChange(before, after);
+        }
+
+        if (previousResults is not null)
+        {
+            results = BenchmarkReport.ReplaceRefined(previousResults, results);
+        }
+
+        BenchmarkReport report = new BenchmarkReport(results, options.OptimalBy)
+        {
+            IncludeUnits = options.UnitsColumn,
+            LoadProcess = options.LoadProcess is { } name
+                ? (name, loadProcessRunning && IsProcessRunning(name))
+                : null,
+            Machine = (SystemInfo.CpuName, SystemInfo.WindowsBuild)
+        };
+        Explain.Write(
+            $"the optimal is the lowest {options.OptimalBy.ToString().ToLowerInvariant()} among {results.Count} valid results, with ties broken by the lower STDEV");
+
+        // Everything below works from the report in memory, so --no-save only skips this write
+        if (!options.NoSave)
+        {
+            await File.WriteAllTextAsync(options.ResultsPath, report.ToCsv());
+
+            // The CSV stays the primary output; the JSON next to it is for tools that ingest the results
+            if (options.Format == ResultsFormat.Json)
+            {
+                await File.WriteAllTextAsync(options.JsonResultsPath, report.ToJson());
+            }
+        }
+
+        if (aborted)
+        {
+            Console.WriteLine(options.NoSave
+                ? "info: benchmark aborted"
+                : $"info: benchmark aborted, partial results saved in {options.ResultsPath}");
+            Environment.ExitCode = 1;
+            if (options.JsonSummary)
+            {
+                _jsonOut.WriteLine(JsonLinesStream.FormatSummary(false, report.Optimal, results.Count, RunTime.Elapsed,
+                    bootConfiguration?.UsePlatformClock));
+            }
+
+            return;
+        }
+
+        if (report.Optimal is null)
+        {
+            FailNothingMeasured(options, helperDirectory, lastFailure);
+        }
+
+        Console.WriteLine();
+        report.PrintTable(options.SortBy, options.OptimalBand, options.DeltaThresholds);
+        Console.WriteLine();
+        if (!options.NoSave)
+        {
+            Console.WriteLine(options.Format == ResultsFormat.Json
+                ? $"info: results saved in {options.ResultsPath} and {options.JsonResultsPath}"
+                : $"info: results saved in {options.ResultsPath}");
+        }
+
+        if (coreResults.Count > 0)
+        {
+            ReportCoreTypes(coreResults);
+        }
+
+        if (skipped > 0)
+        {
+            Console.WriteLine(
+                $"warning: {skipped} of {(options.AdaptiveIncrement is null ? sweepIterations : results.Count + skipped)} resolution measurements were skipped because they failed after {options.Retries} retries");
+        }
+
+        int throttledRows = results.Count(r => r.Throttled);
+        if (throttledRows > 0)
+        {
+            Console.WriteLine(
+                $"warning: {throttledRows} of {results.Count} rows were measured while the CPU was throttled, let it cool down or use --cooldown-ms and re-run them");
+        }
+
+        List<BenchmarkResult> suspicious = results.Where(r => r.DeltaMs > r.ResolutionMs * SuspiciousDeltaFactor).ToList();
+        if (suspicious.Count > 0)
+        {
+            Console.WriteLine(
+                $"warning: {suspicious.Count} of {results.Count} rows have a delta over {SuspiciousDeltaFactor}x the requested resolution (worst: {NumberFormat.Round(suspicious.Max(r => r.DeltaMs))}ms at {suspicious.MaxBy(r => r.DeltaMs).ResolutionMs}ms)");
+            Console.WriteLine("    the requested resolution was most likely not in effect, common causes are:");
+            Console.WriteLine("    - a power saving plan or laptop battery mode throttling the CPU");
+            Console.WriteLine("    - the HPET or dynamic tick boot settings (see --compare-hpet)");
+            Console.WriteLine("    - background load or another program changing the timer resolution");
+            if (SystemInfo.GlobalTimerResolutionRequests != 1)
+            {
+                Console.WriteLine("    - Windows ignoring other processes' requests because GlobalTimerResolutionRequests is not set");
+            }
+        }
+
+        if (report.Optimal is { } optimal)
+        {
+            Console.WriteLine(
+                $"info: optimal resolution is {optimal.ResolutionMs}ms (delta: {NumberFormat.Round(optimal.DeltaMs)}ms, STDEV: {NumberFormat.Round(optimal.Stdev)})");
+            Console.WriteLine("info: to apply it on every boot, place a shortcut in shell:startup with the target:");
+            Console.WriteLine(
+                $"    \"{Path.Combine(helperDirectory, "SetTimerResolution.exe")}\" --no-console --resolution {optimal.Resolution}");
+
+            // A minimum on the boundary of the sweep probably continues past it, so the real optimum was not tested
+            if (!options.StandardSet && parameters.ResolutionList is null && results.Count >= 2)
+            {
+                if (optimal.ResolutionMs <= results.Min(r => r.ResolutionMs))
+                {
+                    Console.WriteLine(
+                        $"warning: the optimal is the lowest resolution tested, lower StartValue below {optimal.ResolutionMs}ms to check whether it keeps improving");
+                }
+                else if (optimal.ResolutionMs >= results.Max(r => r.ResolutionMs))
+                {
+                    Console.WriteLine(
+                        $"warning: the optimal is the highest resolution tested, raise EndValue above {optimal.ResolutionMs}ms to check whether it keeps improving");
+                }
+            }
+        }
+
+        if (report.Spread is { } spread && results.Count > 1)
+        {
+            Console.WriteLine($"info: spread between the best and worst resolution: {NumberFormat.Round(spread)}ms");
+            Console.WriteLine(spread < InsensitiveSpreadMs
+                ? "info: this system is largely insensitive to the timer resolution, any value in the range performs about the same"
+                : "info: the timer resolution makes a measurable difference on this system, applying the optimal is worthwhile");
+        }
+
+        if (options.TraceOptimal)
+        {
+            if (options.TraceOptimalPath is { } tracePath)
+            {
+                await File.WriteAllTextAsync(tracePath, report.ToOptimalTraceJson());
+                Console.WriteLine($"info: optimal selection trace saved in {tracePath}");
+            }
+            else
+            {
+                Console.WriteLine(report.ToOptimalTraceJson());
+            }
+        }
+
+        if (options.PlotPath is { } plotPath)
+        {
+            await ChartRenderer.RenderAsync(report, plotPath);
+            Console.WriteLine($"info: chart saved in {plotPath}");
+        }
+
+        if (options.SummaryImagePath is { } summaryImagePath)
+        {
+            await SummaryImage.RenderAsync(report, summaryImagePath, options.SortBy, options.OptimalBand);
+            Console.WriteLine($"info: summary image saved in {summaryImagePath}");
+        }
+
+        if (options.SqlitePath is { } sqlitePath)
+        {
+            await SqliteExport.WriteAsync(sqlitePath, report);
+        }
+
+        if (options.HistoryPath is { } historyPath)
+        {
+            await History.AppendAsync(historyPath, report);
+        }
+
+        if (options.TargetDelta is { } targetDelta)
+        {
+            bool metTarget = report.Optimal is { } best && best.DeltaMs <= targetDelta;
+            Console.ForegroundColor = metTarget ? ConsoleColor.Green : ConsoleColor.Red;
+            Console.WriteLine(metTarget
+                ? $"{Glyphs.Ok} optimal delta is within the {targetDelta}ms target"
+                : $"{Glyphs.Failed} optimal delta did not meet the {targetDelta}ms target");
+            Console.ResetColor();
+
+            if (!metTarget)
+            {
+                Environment.ExitCode = 1;
+            }
+        }
+
+        if (options.CompareHpet != CompareHpetStage.None)
+        {
+            if (bootConfiguration is null)
+            {
+                await Console.Error.WriteLineAsync(
+                    "error: --compare-hpet needs the platform clock status, which could not be determined");
+                Environment.ExitCode = 1;
+            }
+            else if (options.CompareHpet == CompareHpetStage.Start)
+            {
+                await HpetComparison.SaveAsync(bootConfiguration, report);
+            }
+            else if (await HpetComparison.CompareAsync(bootConfiguration, report) is not { } improvement)
+            {
+                // No comparison means no verdict, so CI cannot pass or fail on it
+                if (options.FailOnNoImprovementPercent is not null)
+                {
+                    await Console.Error.WriteLineAsync(
+                        "error: --fail-on-no-improvement has no verdict because the HPET comparison could not be made");
+                }
+
+                Environment.ExitCode = 1;
+            }
+            else if (options.FailOnNoImprovementPercent is { } requiredImprovement)
+            {
+                // Improvements within the threshold are indistinguishable from run-to-run noise
+                bool helped = improvement > requiredImprovement;
+                Console.ForegroundColor = helped ? ConsoleColor.Green : ConsoleColor.Red;
+                Console.WriteLine(helped
+                    ? $"{Glyphs.Ok} disabling HPET helped on this system (more than {requiredImprovement}% better)"
+                    : $"{Glyphs.Failed} disabling HPET did NOT help on this system (not more than {requiredImprovement}% better)");
+                Console.ResetColor();
+
+                if (!helped)
+                {
+                    Environment.ExitCode = 1;
+                }
+            }
+        }
+
+        if (options.BaselinePath is { } baselinePath
+            && !await Baseline.CheckAsync(report, baselinePath, options.FailThresholdPercent))
+        {
+            Environment.ExitCode = 1;
+        }
+
+        // Last line of output, so wrappers can take the final line without parsing results files
+        if (options.JsonSummary)
+        {
+            _jsonOut.WriteLine(JsonLinesStream.FormatSummary(Environment.ExitCode == 0, report.Optimal, results.Count,
+                RunTime.Elapsed, bootConfiguration?.UsePlatformClock));
+        }
+
+        if (!options.NoSave && !options.Headless && !Console.IsInputRedirected && !Console.IsOutputRedirected)
+        {
+            PromptOpenResults(Path.GetFullPath(options.ResultsPath));
+        }
+    }
+
+    // Errors always go to stderr; in JSON mode they are also written to stdout as an object scripts can parse, and
+    // --json-summary still ends the output with its line
+    [DoesNotReturn]
+    private static void Fail(CommandLineOptions options, string error, string detail, string message)
+    {
+        Console.Error.WriteLine($"error: {message}");
+
+        if (options.JsonToStdout)
+        {
+            _jsonOut.WriteLine(JsonLinesStream.FormatError(error, detail));
+        }
+
+        if (options.JsonSummary)
+        {
+            _jsonOut.WriteLine(JsonLinesStream.FormatSummary(false, null, 0, RunTime.Elapsed, null, error));
+        }
+
+        Environment.Exit(1);
+    }
+
+    // Every iteration changes the global timer resolution, so show whether the system was left where it started
+    private static void ReportResolutionChange(TimerResolution before, TimerResolution after)
+    {
+        if (before.Current == after.Current)
+        {
+            Console.WriteLine($"info: global timer resolution restored to {after.CurrentMs}ms");
+            return;
+        }
+
+        // Every request made by the benchmark has been withdrawn by now, so the difference comes from another process
+        Console.WriteLine(
+            $"warning: global timer resolution changed from {before.CurrentMs}ms to {after.CurrentMs}ms ({(after.Current - (double)before.Current) / 1E4:+0.####;-0.####}ms), another process changed it during the run");
+    }
+
+    // Every resolution failing points at the measurement rather than the timer, so say how to check it
+    [DoesNotReturn]
+    private static void FailNothingMeasured(CommandLineOptions options, string helperDirectory, string? failure)
+    {
+        string measureSleep = Path.Combine(helperDirectory, "MeasureSleep.exe");
+        string hint =
+            $"{Environment.NewLine}This usually means MeasureSleep.exe is broken or prints output this version cannot parse:" +
+            $"{Environment.NewLine}  1. Run \"{measureSleep}\" --samples 10 and check it prints lines such as" +
+            $"{Environment.NewLine}     'Resolution: 0.5000ms, Sleep(1) slept 1.0100ms (delta: 0.0100)' followed by 'Avg:' and 'STDEV:'" +
+            $"{Environment.NewLine}  2. Download MeasureSleep.exe and SetTimerResolution.exe again and check that antivirus has not blocked them" +
+            $"{Environment.NewLine}  3. Pass --sleep-durations 1 to measure in-process without MeasureSleep.exe";
+        string lastError = failure is null ? string.Empty : $" (last error: {failure})";
+
+        Fail(options, "nothing_measured", failure ?? options.ResultsPath,
+            $"no resolution produced a valid measurement{lastError}{hint}");
+    }
+
+    // Last resort for unattended runs that hang: Windows drops a process's timer resolution request when the process
+    // exits, so ending the helpers and this process leaves the timer as it was before the run
+    private static Timer StartGlobalTimeout(CommandLineOptions options, TimeSpan remaining, Process? loadGenerator)
+    {
+        return new Timer(_ =>
+        {
+            if (loadGenerator is { HasExited: false })
+            {
+                loadGenerator.Kill(entireProcessTree: true);
+            }
+
+            KillProcess("SetTimerResolution");
+            KillProcess("MeasureSleep");
+            Fail(options, "global_timeout", options.GlobalTimeout.ToString()!,
+                $"the run exceeded --global-timeout of {options.GlobalTimeout}, terminating");
+        }, null, remaining < TimeSpan.Zero ? TimeSpan.Zero : remaining, Timeout.InfiniteTimeSpan);
+    }
+
+    private static void PromptOpenResults(string path)
+    {
+        Console.Write("Open results now? (y/n) ");
+        string? answer = Console.ReadLine()?.Trim();
+
+        if (!string.Equals(answer, "y", StringComparison.OrdinalIgnoreCase))
+        {
+            return;
+        }
+
+        try
+        {
+            Process.Start(new ProcessStartInfo(path) { UseShellExecute = true })?.Dispose();
+        }
+        catch (Win32Exception e)
+        {
+            Console.Error.WriteLine($"error: unable to open {path}: {e.Message}");
+        }
+    }
+
+    // Builds a combined results file from several runs, e.g. of the same sweep across reboots
+    private static async Task MergeResultsAsync(CommandLineOptions options)
+    {
+        List<IReadOnlyList<BenchmarkResult>> runs = new List<IReadOnlyList<BenchmarkResult>>();
+
+        foreach (string path in options.MergePaths)
+        {
+            if (!File.Exists(path))
+            {
+                Fail(options, "missing_results", path, $"{path} does not exist");
+            }
+
+            runs.Add(BenchmarkReport.ParseCsv(await File.ReadAllTextAsync(path)));
+        }
+
+        BenchmarkReport report = BenchmarkReport.Merge(runs);
+        if (report.Optimal is not { } optimal)
+        {
+            Fail(options, "no_valid_results", string.Join(", ", options.MergePaths), "the results files contain no rows");
+            return;
+        }
+
+        string mergedPath = options.OutputPath ?? MergedResultsPath;
+        await File.WriteAllTextAsync(mergedPath, report.ToCsv());
+
+        report.PrintTable(options.SortBy, options.OptimalBand, options.DeltaThresholds);
+        Console.WriteLine();
+        Console.WriteLine($"info: merged {runs.Count} runs into {mergedPath}");
+        Console.WriteLine(
+            $"info: combined optimal resolution is {optimal.ResolutionMs}ms (delta: {NumberFormat.Round(optimal.DeltaMs)}ms, STDEV: {NumberFormat.Round(optimal.Stdev)})");
+    }
+
+    // Turns samples from another measurement tool into a row of the results file, replacing any row at that resolution
+    private static async Task ImportRawSamplesAsync(CommandLineOptions options, string rawSamplesPath)
+    {
+        if (!File.Exists(rawSamplesPath))
+        {
+            Fail(options, "missing_samples", rawSamplesPath, $"{rawSamplesPath} does not exist");
+        }
+
+        Measurement measurement;
+        try
+        {
+            measurement = Measurement.ParseRawSamples(await File.ReadAllTextAsync(rawSamplesPath));
+        }
+        catch (FormatException e)
+        {
+            Fail(options, "invalid_samples", rawSamplesPath, $"{rawSamplesPath}: {e.Message}");
+            return;
+        }
+
+        if (!measurement.IsValid)
+        {
+            Fail(options, "invalid_samples", rawSamplesPath, $"{rawSamplesPath} contains no samples");
+        }
+
+        int resolution = ResolutionSweep.ToUnits(options.RawSamplesResolutionMs ?? 0, options.Rounding);
+        BenchmarkResult imported =
+            new BenchmarkResult(resolution / 1E4, resolution, measurement.Avg, measurement.Stdev,
+                Samples: measurement.Samples.Count);
+
+        List<BenchmarkResult> results = File.Exists(options.ResultsPath)
+            ? BenchmarkReport.ParseCsv(await File.ReadAllTextAsync(options.ResultsPath))
+            : new List<BenchmarkResult>();
+        results.RemoveAll(r => r.Resolution == resolution);
+        results.Add(imported);
+        results.Sort((a, b) => a.Resolution.CompareTo(b.Resolution));
+
+        BenchmarkReport report = new BenchmarkReport(results);
+        await File.WriteAllTextAsync(options.ResultsPath, report.ToCsv());
+
+        Console.WriteLine(
+            $"info: {measurement.Samples.Count} samples at {imported.ResolutionMs}ms: delta {NumberFormat.Round(imported.DeltaMs)}ms, STDEV {NumberFormat.Round(imported.Stdev)}");
+        report.PrintTable(options.SortBy, options.OptimalBand, options.DeltaThresholds);
+        Console.WriteLine($"info: results saved in {options.ResultsPath}");
+    }
+
+    // Applies a known resolution without benchmarking, either held by this process or left to SetTimerResolution
+    private static async Task SetResolutionAsync(CommandLineOptions options)
+    {
+        int resolution = ResolutionSweep.ToUnits(options.SetResolutionMs, options.Rounding);
+
+        if (options.Hold)
+        {
+            if (TimerResolution.Set((uint)resolution) is not { } granted)
+            {
+                Fail(options, "set_failed", resolution.ToString(), $"unable to set the timer resolution to {resolution / 1E4}ms");
+                return;
+            }
+
+            Console.WriteLine($"info: requested {resolution / 1E4}ms, granted {granted / 1E4}ms");
+            Console.WriteLine("info: holding the resolution, press Ctrl+C to release it");
+
+            TaskCompletionSource released = new TaskCompletionSource();
+            Console.CancelKeyPress += (_, e) =>
+            {
+                e.Cancel = true;
+                released.TrySetResult();
+            };
+            await released.Task;
+            return;
+        }
+
+        string setTimerResolution =
+            Path.Combine(HelperDirectory(options, new[] { "SetTimerResolution.exe" }), "SetTimerResolution.exe");
+        if (!File.Exists(setTimerResolution))
+        {
+            Fail(options, "missing_dependency", "SetTimerResolution.exe",
+                $"{setTimerResolution} does not exist, use --hold to set it from this process");
+        }
+
+        KillProcess("SetTimerResolution");
+        Process.Start(setTimerResolution, $"--resolution {resolution} --no-console")?.Dispose();
+
+        // Give SetTimerResolution a moment to make its request before reading back the granted value
+        await Task.Delay(100);
+        Console.WriteLine(TimerResolution.Query() is { } current
+            ? $"info: requested {resolution / 1E4}ms, granted {current.CurrentMs}ms"
+            : $"info: requested {resolution / 1E4}ms");
+        Console.WriteLine("info: SetTimerResolution keeps running in the background to hold it");
+    }
+
+    private static void ReportCoreTypes(Dictionary<byte, List<BenchmarkResult>> coreResults)
+    {
+        List<BenchmarkResult> optima = new List<BenchmarkResult>();
+
+        foreach ((byte efficiencyClass, List<BenchmarkResult> classResults) in coreResults.OrderBy(pair => pair.Key))
+        {
+            if (new BenchmarkReport(classResults).Optimal is { } optimal)
+            {
+                optima.Add(optimal);
+                Console.WriteLine(
+                    $"info: efficiency class {efficiencyClass} optimal: {optimal.ResolutionMs}ms (delta: {NumberFormat.Round(optimal.DeltaMs)}ms, STDEV: {NumberFormat.Round(optimal.Stdev)})");
+            }
+        }
+
+        Console.WriteLine(optima.Select(optimal => optimal.Resolution).Distinct().Count() > 1
+            ? "info: the optimal resolution differs between core types"
+            : "info: all core types share the same optimal resolution");
+    }
+
+    // MeasureSleep only measures a fixed number of Sleep(1) calls, anything else is measured in-process
+    private static bool UsesMeasureSleep(CommandLineOptions options)
+    {
+        return options.SleepDurations is null && !options.AdaptiveSamples && options.TimePerResolution is null;
+    }
+
+    private static async Task<Measurement> MeasureAsync(string helperDirectory, BenchmarkingParameters parameters,
+        CommandLineOptions options)
+    {
+        if (UsesMeasureSleep(options))
+        {
+            SampleProgress? sleepProgress =
+                SampleProgress.Start(options, parameters.SampleValue + options.DiscardSamples);
+            Measurement sleepMeasurement = await MeasureSleepAsync(helperDirectory, parameters.SampleValue,
+                options.DiscardSamples, options.Priority, sleepProgress);
+            sleepProgress?.Complete();
+
+            if (sleepMeasurement.DiscardIgnored)
+            {
+                Console.WriteLine("warning: no per-sample values were measured, --discard-samples has no effect");
+            }
+
+            return TrimOutliers(sleepMeasurement, options);
+        }
+
+        IReadOnlyList<double> durations = options.SleepDurations ?? new[] { 1.0 };
+        List<double> pooled = new List<double>();
+
+        foreach (double duration in durations)
+        {
+            // Adaptive and time-budgeted runs do not know their sample count up front
+            SampleProgress? progress = SampleProgress.Start(options,
+                options.AdaptiveSamples || options.TimePerResolution is not null ? null : parameters.SampleValue);
+            Action? onSample = progress is null ? null : progress.Advance;
+            long measureStart = Stopwatch.GetTimestamp();
+            Measurement measurement = await Task.Run(() => options switch
+            {
+                { AdaptiveSamples: true } => NativeMeasurer.MeasureAdaptive(duration, options.TargetSem,
+                    options.MaxSamples, options.DiscardSamples, onSample),
+                { TimePerResolution: { } budget } => NativeMeasurer.MeasureFor(duration, budget / durations.Count,
+                    options.DiscardSamples, onSample),
+                _ => NativeMeasurer.Measure(duration, parameters.SampleValue, options.DiscardSamples, onSample)
+            });
+            PhaseTiming.Record("measure", measureStart);
+            progress?.Complete();
+            Console.WriteLine(
+                $"info:   {duration}ms wait: delta {NumberFormat.Round(measurement.Avg)}ms, STDEV {NumberFormat.Round(measurement.Stdev)} ({measurement.Samples.Count} samples)");
+            pooled.AddRange(measurement.Samples);
+        }
+
+        return TrimOutliers(Measurement.FromSamples(pooled), options);
+    }
+
+    private static Measurement TrimOutliers(Measurement measurement, CommandLineOptions options)
+    {
+        if (options.TrimPercent is not { } percent)
+        {
+            return measurement;
+        }
+
+        // Without per-sample lines only MeasureSleep's own Avg and STDEV are known
+        if (measurement.Samples.Count == 0)
+        {
+            Console.WriteLine("warning: no per-sample values were measured, --trim-percent has no effect");
+            return measurement;
+        }
+
+        return measurement.Trim(percent);
+    }
+
+    private static async Task<Measurement> MeasureSleepAsync(string helperDirectory, int samples, int discardSamples,
+        ProcessPriorityClass? priority, SampleProgress? progress = null)
+    {
+        long measureStart = Stopwatch.GetTimestamp();
+        ProcessStartInfo startInfo = new ProcessStartInfo
+        {
+            FileName = Path.Combine(helperDirectory, "MeasureSleep.exe"),
+            Arguments = $"--samples {samples + discardSamples}",
+            UseShellExecute = false,
+            RedirectStandardOutput = true
+        };
+
+        using Process process = Process.Start(startInfo)
+                                ?? throw new InvalidOperationException("MeasureSleep.exe failed to start");
+        if (priority is { } priorityClass)
+        {
+            process.PriorityClass = priorityClass;
+        }
+
+        // Read line by line so every per-sample line MeasureSleep prints can advance the progress
+        StringBuilder output = new StringBuilder();
+        while (await process.StandardOutput.ReadLineAsync() is { } line)
+        {
+            output.Append(line).Append(Environment.NewLine);
+            if (line.StartsWith("Resolution: "))
+            {
+                progress?.Advance();
+            }
+        }
+
+        await process.WaitForExitAsync();
+        PhaseTiming.Record("measure", measureStart);
+
+        long parseStart = Stopwatch.GetTimestamp();
+        Measurement measurement = Measurement.Parse(output.ToString(), discardSamples);
+        PhaseTiming.Record("parse", parseStart);
+        return measurement;
+    }
+
+    // A process holds a single timer resolution request, so with --native-set each call simply replaces the previous one
+    // and nothing has to be spawned or killed between resolutions
+    // With --hold-in-thread the returned holder keeps the request alive until it is disposed after the measurement
+    private static async Task<ResolutionHolder?> ApplyResolutionAsync(string helperDirectory, int resolution,
+        CommandLineOptions options)
+    {
+        if (options.DryMeasure)
+        {
+            return null;
+        }
+
+        if (options.HoldInThread)
+        {
+            return ResolutionHolder.Start((uint)resolution);
+        }
+
+        if (options.NativeSet)
+        {
+            if (TimerResolution.Set((uint)resolution) is null)
+            {
+                throw new InvalidOperationException($"NtSetTimerResolution rejected {resolution / 1E4}ms");
+            }
+
+            _nativeRequest = (uint)resolution;
+            return null;
+        }
+
+        await Task.Run(() =>
+        {
+            Process.Start(Path.Combine(helperDirectory, "SetTimerResolution.exe"),
+                $"--resolution {resolution} --no-console");
+        });
+
+        return null;
+    }
+
+    // Shows what --config, the "Options" section and the command line resolved to, in the same layout as the configuration file
+    private static string EffectiveConfigJson(BenchmarkingParameters parameters, CommandLineOptions options)
+    {
+        using MemoryStream buffer = new MemoryStream();
+        using (Utf8JsonWriter json = new Utf8JsonWriter(buffer, new JsonWriterOptions { Indented = true }))
+        {
+            json.WriteStartObject();
+
+            json.WriteStartObject("BenchmarkingParameters");
+            if (parameters.ResolutionList is { } resolutionList)
+            {
+                json.WriteStartArray("ResolutionList");
+                foreach (double value in resolutionList)
+                {
+                    json.WriteNumberValue(value);
+                }
+
+                json.WriteEndArray();
+            }
+            else
+            {
+                json.WriteNumber("StartValue", parameters.StartValue);
+                json.WriteNumber("IncrementValue", parameters.IncrementValue);
+                json.WriteNumber("EndValue", parameters.EndValue);
+            }
+
+            json.WriteNumber("SampleValue", parameters.SampleValue);
+            json.WriteEndObject();
+
+            json.WriteStartObject("Options");
+            options.WriteJson(json);
+            json.WriteEndObject();
+
+            json.WriteEndObject();
+        }
+
+        return Encoding.UTF8.GetString(buffer.ToArray());
+    }
+
+    // --dependency-dir is searched first, then the working directory, then the folder TimerBenchmark.exe lives in
+    private static string HelperDirectory(CommandLineOptions options, IReadOnlyList<string> dependencies)
+    {
+        List<string> candidates = new List<string> { Environment.CurrentDirectory, AppContext.BaseDirectory };
+        if (options.DependencyDir is { } dependencyDir)
+        {
+            candidates.Insert(0, Path.GetFullPath(dependencyDir));
+        }
+
+        return candidates.FirstOrDefault(directory =>
+                   dependencies.All(dependency => File.Exists(Path.Combine(directory, dependency))))
+               ?? candidates[0];
+    }
+
+    private static bool PathsEqual(string first, string second)
+    {
+        return string.Equals(Path.TrimEndingDirectorySeparator(Path.GetFullPath(first)),
+            Path.TrimEndingDirectorySeparator(Path.GetFullPath(second)), StringComparison.OrdinalIgnoreCase);
+    }
+
+    private static bool IsProcessRunning(string processName)
+    {
+        Process[] processes = Process.GetProcessesByName(Path.GetFileNameWithoutExtension(processName));
+        foreach (Process process in processes)
+        {
+            process.Dispose();
+        }
+
+        return processes.Length > 0;
     }
 
+    // Kill only starts the termination, and a timer resolution request lasts until the process is actually gone
     private static void KillProcess(string processName)
     {
         foreach (var process in Process.GetProcessesByName(processName))
         {
             process.Kill();
+            process.WaitForExit(KillTimeoutMs);
         }
     }
 }
\ No newline at end of file
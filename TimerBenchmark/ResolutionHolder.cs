@@ -0,0 +1,52 @@
+﻿namespace TimerBenchmark;
+
+// Requests a resolution from a dedicated high-priority thread and keeps it until disposed, the way a game holds one
+// for its whole session instead of setting it just before measuring
+internal sealed class ResolutionHolder : IDisposable
+{
+    private readonly ManualResetEventSlim _release = new ManualResetEventSlim();
+    private readonly Thread _thread;
+
+    private ResolutionHolder(uint resolution)
+    {
+        using ManualResetEventSlim requested = new ManualResetEventSlim();
+        uint? granted = null;
+
+        _thread = new Thread(() =>
+        {
+            granted = TimerResolution.Set(resolution);
+            requested.Set();
+
+            if (granted is not null)
+            {
+                _release.Wait();
+                TimerResolution.Release(resolution);
+            }
+        })
+        {
+            IsBackground = true,
+            Priority = ThreadPriority.Highest,
+            Name = "ResolutionHolder"
+        };
+        _thread.Start();
+        requested.Wait();
+
+        if (granted is null)
+        {
+            _thread.Join();
+            throw new InvalidOperationException($"NtSetTimerResolution rejected {resolution / 1E4}ms");
+        }
+    }
+
+    public static ResolutionHolder Start(uint resolution)
+    {
+        return new ResolutionHolder(resolution);
+    }
+
+    public void Dispose()
+    {
+        _release.Set();
+        _thread.Join();
+        _release.Dispose();
+    }
+}
@@ -0,0 +1,142 @@
+﻿namespace TimerBenchmark;
+
+// Turns the configured range or list into the resolutions to request, and those into the 100ns units the timer API takes
+internal static class ResolutionSweep
+{
+    // Each --adaptive level divides the step by this much and probes this many steps either side of the best result
+    public const int AdaptiveRefinementFactor = 5;
+
+    // Tolerates binary noise such as (0.6 - 0.5) / 0.001 evaluating to 99.99999999999997
+    private const double Epsilon = 1E-9;
+
+    // Timer resolutions are set in 100ns units. Nearest can request a slightly finer resolution than asked for,
+    // floor always requests an equal or finer one and ceil never requests a finer one.
+    public static int ToUnits(double resolutionMs, RoundingMode rounding)
+    {
+        // Round away binary noise first so 0.5029ms becomes 5029 rather than 5028.9999...
+        double units = Math.Round(resolutionMs * 1E4, 6);
+
+        return (int)(rounding switch
+        {
+            RoundingMode.Floor => Math.Floor(units),
+            RoundingMode.Ceil => Math.Ceiling(units),
+            _ => Math.Round(units, MidpointRounding.AwayFromZero)
+        });
+    }
+
+    // Values yields nothing for such a range, which would only surface at the end as "no valid results"
+    public static string? RangeError(BenchmarkingParameters parameters)
+    {
+        if (parameters.IncrementValue <= 0)
+        {
+            return $"IncrementValue must be positive, got {parameters.IncrementValue}";
+        }
+
+        if (parameters.EndValue <= parameters.StartValue)
+        {
+            return $"EndValue must be greater than StartValue, got StartValue {parameters.StartValue} and EndValue {parameters.EndValue}";
+        }
+
+        return null;
+    }
+
+    public static string? ListError(IReadOnlyList<double> resolutionList)
+    {
+        return resolutionList.Any(value => value <= 0)
+            ? $"ResolutionList must only contain positive values, got {string.Join(", ", resolutionList)}"
+            : null;
+    }
+
+    // The warning is returned rather than printed, so only the run itself reports it
+    public static List<double> Values(BenchmarkingParameters parameters, out string? warning)
+    {
+        warning = null;
+        double steps = (parameters.EndValue - parameters.StartValue) / parameters.IncrementValue;
+        int wholeSteps = (int)Math.Floor(steps + Epsilon);
+        if (wholeSteps < 0)
+        {
+            return new List<double>();
+        }
+
+        List<double> values = new List<double>(wholeSteps + 2);
+
+        // Multiplying instead of accumulating keeps rounding errors from drifting past the end value
+        for (int step = 0; step <= wholeSteps; step++)
+        {
+            values.Add(parameters.StartValue + step * parameters.IncrementValue);
+        }
+
+        // The shortened last step then makes up the whole sweep, which is easy to mistake for a complete run
+        if (wholeSteps == 0 && steps > Epsilon)
+        {
+            warning =
+                $"increment {parameters.IncrementValue} is larger than the range {parameters.StartValue}-{parameters.EndValue}, only {parameters.StartValue}ms and {parameters.EndValue}ms are tested, lower IncrementValue to test the resolutions between them";
+            values.Add(parameters.EndValue);
+        }
+        else if (steps - wholeSteps > Epsilon)
+        {
+            warning =
+                $"increment {parameters.IncrementValue} does not divide {parameters.StartValue}-{parameters.EndValue} evenly, the last step is shortened to end exactly at {parameters.EndValue}";
+            values.Add(parameters.EndValue);
+        }
+
+        return values;
+    }
+
+    // Coarse-to-fine search: the regular sweep first, then ever finer steps around the best result so far until the
+    // step reaches finestIncrement. Reads results while the sweep loop fills it, so must be enumerated lazily
+    public static IEnumerable<double> AdaptiveValues(IReadOnlyList<double> coarseValues,
+        BenchmarkingParameters parameters, double finestIncrement, List<BenchmarkResult> results,
+        RoundingMode rounding, OptimalMetric optimalBy)
+    {
+        HashSet<int> probed = new HashSet<int>();
+        double increment = parameters.IncrementValue;
+        IEnumerable<double> level = coarseValues;
+
+        while (true)
+        {
+            foreach (double value in level)
+            {
+                // Finer levels land on resolutions a coarser one already measured
+                if (probed.Add(ToUnits(value, rounding)))
+                {
+                    yield return value;
+                }
+            }
+
+            if (increment <= finestIncrement || new BenchmarkReport(results, optimalBy).Optimal is not { } best)
+            {
+                yield break;
+            }
+
+            double window = increment;
+            (increment, int steps) = Refine(increment, finestIncrement);
+            Console.WriteLine($"info: refining around {best.ResolutionMs}ms in steps of {increment}ms (±{window}ms)");
+
+            level = Enumerable.Range(-steps, 2 * steps + 1)
+                .Select(step => best.ResolutionMs + step * increment)
+                .Where(value => value >= parameters.StartValue && value <= parameters.EndValue)
+                .ToList();
+        }
+    }
+
+    // Worst case for the time estimate: every refinement level probes its full window
+    public static int AdaptiveProbeCount(double increment, double finestIncrement)
+    {
+        int probes = 0;
+        while (increment > finestIncrement)
+        {
+            (increment, int steps) = Refine(increment, finestIncrement);
+            probes += 2 * steps + 1;
+        }
+
+        return probes;
+    }
+
+    // The next level covers ± the previous increment in steps a factor finer, never finer than requested
+    private static (double Increment, int Steps) Refine(double increment, double finestIncrement)
+    {
+        double finer = Math.Max(increment / AdaptiveRefinementFactor, finestIncrement);
+        return (finer, (int)Math.Ceiling(increment / finer));
+    }
+}
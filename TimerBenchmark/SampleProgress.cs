@@ -0,0 +1,48 @@
+﻿using System.Diagnostics;
+
+namespace TimerBenchmark;
+
+// Keeps one console line updated with the sample count so a long resolution visibly advances
+internal sealed class SampleProgress
+{
+    private static readonly TimeSpan RedrawInterval = TimeSpan.FromMilliseconds(100);
+
+    private readonly int? _total;
+    private int _done;
+    private long _lastDraw;
+    private int _width;
+
+    private SampleProgress(int? total)
+    {
+        _total = total;
+    }
+
+    // Progress is only drawn on an interactive console that nothing else is rendering to
+    public static SampleProgress? Start(CommandLineOptions options, int? total)
+    {
+        return Console.IsOutputRedirected || options.Tui || options.JsonToStdout ? null : new SampleProgress(total);
+    }
+
+    public void Advance()
+    {
+        _done++;
+
+        if (Stopwatch.GetElapsedTime(_lastDraw) < RedrawInterval && _done != _total)
+        {
+            return;
+        }
+
+        _lastDraw = Stopwatch.GetTimestamp();
+        string line = _total is { } total ? $"  sampling {_done}/{total}" : $"  sampling {_done}";
+        _width = Math.Max(_width, line.Length);
+        Console.Write($"\r{line.PadRight(_width)}");
+    }
+
+    public void Complete()
+    {
+        if (_width > 0)
+        {
+            Console.Write($"\r{new string(' ', _width)}\r");
+        }
+    }
+}
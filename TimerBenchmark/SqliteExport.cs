@@ -0,0 +1,79 @@
+﻿using System.Globalization;
+using Microsoft.Data.Sqlite;
+
+namespace TimerBenchmark;
+
+internal static class SqliteExport
+{
+    private const string Schema = """
+        CREATE TABLE IF NOT EXISTS runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL,
+            cpu TEXT NOT NULL,
+            windows_build INTEGER NOT NULL,
+            optimal_resolution_ms REAL,
+            optimal_delta_ms REAL,
+            optimal_stdev REAL
+        );
+        CREATE TABLE IF NOT EXISTS results (
+            run_id INTEGER NOT NULL REFERENCES runs(id),
+            resolution_ms REAL NOT NULL,
+            delta_ms REAL NOT NULL,
+            stdev REAL NOT NULL
+        );
+        """;
+
+    public static async Task WriteAsync(string path, BenchmarkReport report)
+    {
+        await using SqliteConnection connection = new SqliteConnection($"Data Source={path}");
+        await connection.OpenAsync();
+
+        await using (SqliteCommand schema = connection.CreateCommand())
+        {
+            schema.CommandText = Schema;
+            await schema.ExecuteNonQueryAsync();
+        }
+
+        await using SqliteTransaction transaction = (SqliteTransaction)await connection.BeginTransactionAsync();
+
+        long runId;
+        await using (SqliteCommand run = connection.CreateCommand())
+        {
+            run.CommandText = """
+                INSERT INTO runs (timestamp, cpu, windows_build, optimal_resolution_ms, optimal_delta_ms, optimal_stdev)
+                VALUES ($timestamp, $cpu, $build, $resolution, $delta, $stdev);
+                SELECT last_insert_rowid();
+                """;
+            run.Parameters.AddWithValue("$timestamp", DateTime.Now.ToString("s", CultureInfo.InvariantCulture));
+            run.Parameters.AddWithValue("$cpu", SystemInfo.CpuName);
+            run.Parameters.AddWithValue("$build", SystemInfo.WindowsBuild);
+            run.Parameters.AddWithValue("$resolution", (object?)report.Optimal?.ResolutionMs ?? DBNull.Value);
+            run.Parameters.AddWithValue("$delta", (object?)report.Optimal?.DeltaMs ?? DBNull.Value);
+            run.Parameters.AddWithValue("$stdev", (object?)report.Optimal?.Stdev ?? DBNull.Value);
+            runId = (long)(await run.ExecuteScalarAsync())!;
+        }
+
+        await using (SqliteCommand insert = connection.CreateCommand())
+        {
+            insert.CommandText = """
+                INSERT INTO results (run_id, resolution_ms, delta_ms, stdev)
+                VALUES ($run, $resolution, $delta, $stdev);
+                """;
+            insert.Parameters.AddWithValue("$run", runId);
+            SqliteParameter resolution = insert.Parameters.Add("$resolution", SqliteType.Real);
+            SqliteParameter delta = insert.Parameters.Add("$delta", SqliteType.Real);
+            SqliteParameter stdev = insert.Parameters.Add("$stdev", SqliteType.Real);
+
+            foreach (BenchmarkResult result in report.Results)
+            {
+                resolution.Value = result.ResolutionMs;
+                delta.Value = result.DeltaMs;
+                stdev.Value = result.Stdev;
+                await insert.ExecuteNonQueryAsync();
+            }
+        }
+
+        await transaction.CommitAsync();
+        Console.WriteLine($"info: results saved in {path}");
+    }
+}
@@ -0,0 +1,48 @@
+﻿namespace TimerBenchmark;
+
+// Renders the summary table to a PNG so shared results look the same whichever terminal produced them
+internal static class SummaryImage
+{
+    private const int Margin = 16;
+    private const int Scale = 2;
+    private const int LineSpacing = 6;
+
+    private static readonly (byte, byte, byte) Background = (255, 255, 255);
+    private static readonly (byte, byte, byte) TextColor = (40, 40, 40);
+    private static readonly (byte, byte, byte) HeaderColor = (100, 100, 100);
+    private static readonly (byte, byte, byte) OptimalColor = (0, 120, 0);
+    private static readonly (byte, byte, byte) OptimalHighlight = (220, 245, 220);
+    private static readonly (byte, byte, byte) BandColor = (0, 110, 140);
+
+    public static async Task RenderAsync(BenchmarkReport report, string path, SortOrder sortBy, double optimalBand)
+    {
+        List<(string Line, RowKind Kind, double? DeltaMs)> lines = report.TableLines(sortBy, optimalBand).ToList();
+        int lineHeight = PngCanvas.GlyphHeight * Scale + LineSpacing;
+        int width = Margin * 2 + lines.Max(l => l.Line.Length) * PngCanvas.GlyphWidth * Scale;
+        int height = Margin * 2 + lines.Count * lineHeight;
+
+        PngCanvas canvas = new PngCanvas(width, height, Background);
+
+        for (int i = 0; i < lines.Count; i++)
+        {
+            (string line, RowKind kind, _) = lines[i];
+            int y = Margin + i * lineHeight;
+
+            if (kind == RowKind.Optimal)
+            {
+                canvas.FillRect(Margin / 2, y - LineSpacing / 2, width - Margin, lineHeight, OptimalHighlight);
+            }
+
+            (byte, byte, byte) color = kind switch
+            {
+                RowKind.Header => HeaderColor,
+                RowKind.Optimal => OptimalColor,
+                RowKind.WithinBand => BandColor,
+                _ => TextColor
+            };
+            canvas.DrawText(Margin, y, line, color, Scale);
+        }
+
+        await canvas.SaveAsync(path);
+    }
+}
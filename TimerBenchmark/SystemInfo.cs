@@ -0,0 +1,99 @@
+﻿using System.Runtime.Intrinsics.X86;
+using Microsoft.Win32;
+
+namespace TimerBenchmark;
+
+internal static class SystemInfo
+{
+    private const string ProcessorKey = @"HARDWARE\DESCRIPTION\System\CentralProcessor\0";
+    private const string KernelKey = @"SYSTEM\CurrentControlSet\Control\Session Manager\kernel";
+    private const string PolicyKey = @"SOFTWARE\Microsoft\Windows\CurrentVersion\Policies\System";
+    private const string DeviceGuardKey = @"SYSTEM\CurrentControlSet\Control\DeviceGuard";
+    private const string HvciKey = DeviceGuardKey + @"\Scenarios\HypervisorEnforcedCodeIntegrity";
+
+    // Windows 10 2004 made timer resolution requests per-process
+    private const int PerProcessResolutionBuild = 19041;
+
+    // Windows Server 2022 and Windows 11 read GlobalTimerResolutionRequests to restore the global behavior
+    private const int GlobalRequestsKeyBuild = 20348;
+
+    public static string CpuName
+    {
+        get
+        {
+            using RegistryKey? key = Registry.LocalMachine.OpenSubKey(ProcessorKey);
+
+            if (key?.GetValue("ProcessorNameString") is string brand && !string.IsNullOrWhiteSpace(brand))
+            {
+                return brand.Trim();
+            }
+
+            // Some virtualized CPUs have no brand string, e.g. "AuthenticAMD AMD64 Family 25 Model 33 Stepping 0"
+            string? vendor = key?.GetValue("VendorIdentifier") as string;
+            string? identifier = key?.GetValue("Identifier") as string;
+            string fallback = string.Join(' ', new[] { vendor, identifier }.Where(s => !string.IsNullOrWhiteSpace(s)));
+
+            return fallback.Length > 0 ? fallback : "Unknown";
+        }
+    }
+
+    public static int WindowsBuild => Environment.OSVersion.Version.Build;
+
+    // Lets a process's timer resolution request apply system-wide on Windows Server 2022+ and Windows 11+
+    public static int? GlobalTimerResolutionRequests
+    {
+        get
+        {
+            using RegistryKey? key = Registry.LocalMachine.OpenSubKey(KernelKey);
+            return key?.GetValue("GlobalTimerResolutionRequests") as int?;
+        }
+    }
+
+    // Whether SetTimerResolution's request reaches MeasureSleep, which runs in another process, on this build
+    public static (bool Global, string Explanation) TimerResolutionScope
+    {
+        get
+        {
+            int build = WindowsBuild;
+
+            if (build < PerProcessResolutionBuild)
+            {
+                return (true, $"build {build} predates Windows 10 2004, every request applies system-wide");
+            }
+
+            if (build < GlobalRequestsKeyBuild)
+            {
+                return (false,
+                    $"build {build} applies requests per process and does not support GlobalTimerResolutionRequests, another process's request does not change this one's resolution");
+            }
+
+            return GlobalTimerResolutionRequests == 1
+                ? (true, $"build {build} with GlobalTimerResolutionRequests = 1, requests apply system-wide")
+                : (false,
+                    $"build {build} applies requests per process unless GlobalTimerResolutionRequests is set to 1 in HKLM\\{KernelKey}");
+        }
+    }
+
+    // CPUID leaf 1 sets ECX bit 31 when running under a hypervisor, which includes Windows itself once Hyper-V or VBS is on
+    public static bool HypervisorPresent => X86Base.IsSupported && (X86Base.CpuId(1, 0).Ecx & (1 << 31)) != 0;
+
+    public static bool VbsEnabled => ReadDword(DeviceGuardKey, "EnableVirtualizationBasedSecurity") == 1;
+
+    public static bool HvciEnabled => ReadDword(HvciKey, "Enabled") == 1;
+
+    // EnableLUA defaults to on when the value is missing
+    public static bool IsUacEnabled
+    {
+        get
+        {
+            using RegistryKey? key = Registry.LocalMachine.OpenSubKey(PolicyKey);
+            return key?.GetValue("EnableLUA") is not int enabled || enabled != 0;
+        }
+    }
+
+    private static int? ReadDword(string keyPath, string name)
+    {
+        using RegistryKey? key = Registry.LocalMachine.OpenSubKey(keyPath);
+        return key?.GetValue(name) as int?;
+    }
+}
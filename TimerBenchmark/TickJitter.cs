@@ -0,0 +1,42 @@
+﻿using System.Diagnostics;
+using System.Runtime.InteropServices;
+
+namespace TimerBenchmark;
+
+// The interrupt time only advances on a clock interrupt, so spinning on it and timing each advance with QPC
+// gives the period of the timer tick itself and how much it varies
+internal static class TickJitter
+{
+    // Bounds the spin when dynamic tick suppresses interrupts on an idle system
+    private static readonly TimeSpan MaxDuration = TimeSpan.FromSeconds(2);
+
+    public static Measurement Measure(int ticks)
+    {
+        List<double> periods = new List<double>();
+        long start = Stopwatch.GetTimestamp();
+        long? lastTick = null;
+        QueryInterruptTime(out ulong last);
+
+        while (periods.Count < ticks && Stopwatch.GetElapsedTime(start) < MaxDuration)
+        {
+            QueryInterruptTime(out ulong now);
+            if (now == last)
+            {
+                continue;
+            }
+
+            long timestamp = Stopwatch.GetTimestamp();
+            if (lastTick is { } previous)
+            {
+                periods.Add(Stopwatch.GetElapsedTime(previous, timestamp).TotalMilliseconds);
+            }
+
+            (last, lastTick) = (now, timestamp);
+        }
+
+        return Measurement.FromSamples(periods);
+    }
+
+    [DllImport("kernelbase.dll")]
+    private static extern void QueryInterruptTime(out ulong interruptTime);
+}
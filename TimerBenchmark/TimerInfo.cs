@@ -0,0 +1,46 @@
+﻿using System.ComponentModel;
+using System.Diagnostics;
+
+namespace TimerBenchmark;
+
+// One-stop dump of the timer-relevant system state for --list-timer-info
+internal static class TimerInfo
+{
+    public static async Task PrintAsync()
+    {
+        if (TimerResolution.Query() is { } resolution)
+        {
+            Console.WriteLine($"Coarsest timer resolution: {resolution.CoarsestMs}ms");
+            Console.WriteLine($"Finest timer resolution: {resolution.FinestMs}ms");
+            Console.WriteLine($"Current timer resolution: {resolution.CurrentMs}ms");
+        }
+        else
+        {
+            Console.WriteLine("Timer resolution: unavailable");
+        }
+
+        Console.WriteLine(
+            $"QueryPerformanceCounter frequency: {Stopwatch.Frequency} Hz ({ClockSource.Detect(Stopwatch.Frequency)})");
+
+        try
+        {
+            BootConfiguration bootConfiguration = await BootConfiguration.QueryAsync();
+            Console.WriteLine(
+                $"HPET as platform clock (bcdedit useplatformclock, not the Device Manager device): {(bootConfiguration.UsePlatformClock ? "on" : "off")}");
+            Console.WriteLine($"Dynamic Tick: {(bootConfiguration.DisableDynamicTick ? "disabled" : "enabled")}");
+        }
+        catch (Exception e) when (e is Win32Exception or InvalidOperationException)
+        {
+            Console.WriteLine($"Platform Clock (HPET) and Dynamic Tick: unavailable ({e.Message})");
+        }
+
+        Console.WriteLine(
+            $"GlobalTimerResolutionRequests: {SystemInfo.GlobalTimerResolutionRequests?.ToString() ?? "not set"}");
+        Console.WriteLine($"Windows build: {SystemInfo.WindowsBuild}");
+        (bool global, string scope) = SystemInfo.TimerResolutionScope;
+        Console.WriteLine($"Timer resolution requests: {(global ? "global" : "per-process")} ({scope})");
+        Console.WriteLine($"CPU: {SystemInfo.CpuName}");
+        Console.WriteLine($"Hypervisor: {(SystemInfo.HypervisorPresent ? "present" : "not detected")}");
+        Console.WriteLine($"Virtualization-based security: {(SystemInfo.VbsEnabled ? "on" : "off")}, HVCI: {(SystemInfo.HvciEnabled ? "on" : "off")}");
+    }
+}
@@ -0,0 +1,40 @@
+﻿using System.Runtime.InteropServices;
+
+namespace TimerBenchmark;
+
+// Timer resolutions are expressed in 100ns units; the coarsest is typically 15.625ms and the finest 0.5ms
+internal readonly record struct TimerResolution(uint Coarsest, uint Finest, uint Current)
+{
+    public double CoarsestMs => Coarsest / 1E4;
+    public double FinestMs => Finest / 1E4;
+    public double CurrentMs => Current / 1E4;
+
+    public static TimerResolution? Query()
+    {
+        int status = NtQueryTimerResolution(out uint minimum, out uint maximum, out uint current);
+
+        // ntdll names the coarsest interval the "minimum" resolution and the finest the "maximum"
+        return status == 0 ? new TimerResolution(minimum, maximum, current) : null;
+    }
+
+    // The request only lasts as long as the calling process; returns the granted resolution in 100ns units
+    public static uint? Set(uint desired)
+    {
+        int status = NtSetTimerResolution(desired, true, out uint current);
+        return status == 0 ? current : null;
+    }
+
+    // Withdraws this process's request; Windows falls back to the finest resolution any other process still requests
+    public static void Release(uint desired)
+    {
+        NtSetTimerResolution(desired, false, out _);
+    }
+
+    [DllImport("ntdll.dll")]
+    private static extern int NtSetTimerResolution(uint desiredResolution, bool setResolution,
+        out uint currentResolution);
+
+    [DllImport("ntdll.dll")]
+    private static extern int NtQueryTimerResolution(out uint minimumResolution, out uint maximumResolution,
+        out uint currentResolution);
+}
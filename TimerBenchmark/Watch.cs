@@ -0,0 +1,114 @@
+﻿using System.Diagnostics;
+using System.Globalization;
+
+namespace TimerBenchmark;
+
+// Re-runs the benchmark on an interval to follow how the timer behaves over a session. Every run is a fresh child
+// process, so each one starts from a clean timer state and appends its optimum to the history file
+internal static class Watch
+{
+    private const string DefaultHistoryPath = "history.csv";
+
+    // Set for every run; Watch can also come from the configuration file, which the child reads like any other run
+    public const string ChildVariable = "TIMERRES_WATCH_CHILD";
+
+    public static bool IsChild => Environment.GetEnvironmentVariable(ChildVariable) is not null;
+
+    public static async Task RunAsync(string[] args, TimeSpan interval, CommandLineOptions options)
+    {
+        string historyPath = options.HistoryPath ?? DefaultHistoryPath;
+        List<string> childArgs = ChildArguments(args);
+        if (options.HistoryPath is null)
+        {
+            childArgs.Add("--history");
+            childArgs.Add(historyPath);
+        }
+
+        for (int run = 1; ; run++)
+        {
+            if (options.WatchClear && !Console.IsOutputRedirected)
+            {
+                Console.Clear();
+            }
+
+            if (LatestOptimal(historyPath) is { } latest)
+            {
+                Console.WriteLine($"info: latest optimal: {latest}");
+            }
+
+            Console.WriteLine($"info: watch run {run} started at {DateTime.Now.ToString("T", CultureInfo.InvariantCulture)}");
+            DateTime started = DateTime.Now;
+
+            ProcessStartInfo startInfo = new ProcessStartInfo(Environment.ProcessPath!, childArgs)
+            {
+                UseShellExecute = false,
+                // Keeps the child from prompting to open its results, which would stall the watch
+                RedirectStandardInput = true
+            };
+            startInfo.Environment[ChildVariable] = "1";
+
+            using (Process process = Process.Start(startInfo)
+                                     ?? throw new InvalidOperationException("unable to start the benchmark"))
+            {
+                process.StandardInput.Close();
+                await process.WaitForExitAsync();
+
+                if (process.ExitCode != 0)
+                {
+                    Console.WriteLine($"warning: watch run {run} exited with code {process.ExitCode}");
+                }
+            }
+
+            // The interval is measured from the start of a run, so runs longer than it follow each other directly
+            TimeSpan wait = interval - (DateTime.Now - started);
+            if (wait > TimeSpan.Zero)
+            {
+                Console.WriteLine($"info: next run at {(DateTime.Now + wait).ToString("T", CultureInfo.InvariantCulture)}");
+                await Task.Delay(wait);
+            }
+        }
+    }
+
+    private static List<string> ChildArguments(string[] args)
+    {
+        List<string> childArgs = new List<string>();
+
+        for (int i = 0; i < args.Length; i++)
+        {
+            switch (args[i])
+            {
+                case "--watch":
+                    i++;
+                    break;
+                case "--watch-clear":
+                    break;
+                default:
+                    childArgs.Add(args[i]);
+                    break;
+            }
+        }
+
+        return childArgs;
+    }
+
+    // The last row History.AppendAsync wrote, formatted for one line
+    private static string? LatestOptimal(string historyPath)
+    {
+        if (!File.Exists(historyPath))
+        {
+            return null;
+        }
+
+        string? last = File.ReadLines(historyPath).Skip(1).LastOrDefault(line => line.Length > 0);
+        if (last is null)
+        {
+            return null;
+        }
+
+        // The CPU field is quoted and may contain commas, so read the fixed columns from both ends
+        string[] fields = last.Split(',');
+        return fields.Length < 6
+            ? null
+            : $"{fields[^3]}ms (delta {fields[^2]}ms, STDEV {fields[^1]}) at {fields[0]}";
+    }
+}
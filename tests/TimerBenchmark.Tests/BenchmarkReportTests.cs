@@ -0,0 +1,257 @@
+using System.Text.Json;
+using Xunit;
+
+namespace TimerBenchmark.Tests;
+
+public class BenchmarkReportTests
+{
+    private const double Tolerance = 1E-6;
+
+    [Fact]
+    public void ParseCsv_SkipsHeaderAndConvertsToUnits()
+    {
+        List<BenchmarkResult> results = BenchmarkReport.ParseCsv(Fixture.Read("results-sample.txt"));
+
+        Assert.Equal(new[] { 5000, 5020, 5040, 5060 }, results.Select(r => r.Resolution));
+        Assert.Equal(0.0312, results[0].DeltaMs, Tolerance);
+        Assert.Equal(0.0101, results[0].Stdev, Tolerance);
+    }
+
+    [Fact]
+    public void Optimal_EqualDeltas_PrefersLowerStdev()
+    {
+        BenchmarkReport report = new BenchmarkReport(BenchmarkReport.ParseCsv(Fixture.Read("results-sample.txt")));
+
+        Assert.Equal(5040, report.Optimal?.Resolution);
+    }
+
+    [Fact]
+    public void Optimal_SingleRow_IsThatRow()
+    {
+        BenchmarkReport report = new BenchmarkReport(BenchmarkReport.ParseCsv(Fixture.Read("results-single-row.txt")));
+
+        Assert.Equal(5000, report.Optimal?.Resolution);
+        Assert.Equal(0, report.Spread);
+        Assert.Contains("<- optimal", report.ToTable());
+    }
+
+    [Fact]
+    public void ToTable_SingleRow_IsOptimalForEverySortAndBand()
+    {
+        BenchmarkReport report = new BenchmarkReport(new[] { new BenchmarkResult(0.5, 5000, 0.0123, 0.002) });
+
+        foreach (SortOrder sortBy in Enum.GetValues<SortOrder>())
+        {
+            Assert.Single(report.ToTable(sortBy, optimalBand: 0.01).Split(Environment.NewLine),
+                line => line.Contains("<- optimal"));
+        }
+    }
+
+    [Fact]
+    public void ToTable_DuplicateRows_MarksOnlyTheFirstAsOptimal()
+    {
+        BenchmarkResult row = new BenchmarkResult(0.5, 5000, 0.0123, 0.002);
+        string[] lines = new BenchmarkReport(new[] { row, row }).ToTable().Split(Environment.NewLine);
+
+        Assert.Single(lines, line => line.Contains("<- optimal"));
+    }
+
+    [Fact]
+    public void Optimal_NoResults_IsNull()
+    {
+        BenchmarkReport report = new BenchmarkReport(Array.Empty<BenchmarkResult>());
+
+        Assert.Null(report.Optimal);
+        Assert.Null(report.Spread);
+    }
+
+    [Fact]
+    public void ToTable_MarksOnlyOneOptimalRow()
+    {
+        BenchmarkReport report = new BenchmarkReport(BenchmarkReport.ParseCsv(Fixture.Read("results-sample.txt")));
+        string[] lines = report.ToTable().Split(Environment.NewLine);
+
+        Assert.Single(lines, line => line.Contains("<- optimal"));
+        Assert.Contains(lines, line => line.Contains("0.5040") && line.Contains("<- optimal"));
+    }
+
+    [Fact]
+    public void Spread_IsDifferenceBetweenWorstAndBestDelta()
+    {
+        BenchmarkReport report = new BenchmarkReport(BenchmarkReport.ParseCsv(Fixture.Read("results-sample.txt")));
+
+        Assert.Equal(0.0191, report.Spread!.Value, Tolerance);
+    }
+
+    [Fact]
+    public void ToCsv_RoundTripsThroughParseCsv()
+    {
+        List<BenchmarkResult> results = BenchmarkReport.ParseCsv(Fixture.Read("results-sample.txt"));
+
+        Assert.Equal(results, BenchmarkReport.ParseCsv(new BenchmarkReport(results).ToCsv()));
+    }
+
+    [Fact]
+    public void ToCsvAndToJson_WithLowPrecision_KeepFullDeltas()
+    {
+        BenchmarkResult[] results =
+        {
+            new BenchmarkResult(0.5, 5000, 0.0123456789, 0.00312345, MedianMs: 0.011, MinMs: 0.004, MaxMs: 0.0301),
+            new BenchmarkResult(0.5002, 5002, 0.0234567891, 0.0041234, MedianMs: 0.021, MinMs: 0.005, MaxMs: 0.0402)
+        };
+
+        // --precision only shapes what is printed; files are read back by merge, baselines and the HPET comparison
+        NumberFormat.Digits = 1;
+        try
+        {
+            BenchmarkReport report = new BenchmarkReport(results);
+            using JsonDocument json = JsonDocument.Parse(report.ToJson());
+
+            Assert.Equal(results, BenchmarkReport.ParseCsv(report.ToCsv()));
+            Assert.Equal(0.0123456789, json.RootElement.GetProperty("optimal").GetProperty("deltaMs").GetDouble());
+        }
+        finally
+        {
+            NumberFormat.Digits = NumberFormat.DefaultDigits;
+        }
+    }
+
+    [Fact]
+    public void ToCsv_WithTimestamps_AddsIsoTimestampColumn()
+    {
+        DateTimeOffset measuredAt = new DateTimeOffset(2024, 5, 1, 13, 45, 30, TimeSpan.FromHours(2));
+        BenchmarkReport report = new BenchmarkReport(new[] { new BenchmarkResult(0.5, 5000, 0.01, 0.003, MeasuredAt: measuredAt) });
+        string[] lines = report.ToCsv().Split(Environment.NewLine);
+
+        Assert.EndsWith(",Timestamp", lines[0]);
+        Assert.EndsWith(", 2024-05-01T13:45:30+02:00", lines[1]);
+    }
+
+    [Fact]
+    public void ToCsv_WithOptionalColumns_RoundTripsThroughParseCsv()
+    {
+        DateTimeOffset measuredAt = new DateTimeOffset(2024, 5, 1, 13, 45, 30, TimeSpan.FromHours(2));
+        BenchmarkResult[] results =
+        {
+            new BenchmarkResult(0.5, 5000, 0.01, 0.003, HighResTimerDeltaMs: 0.002, Throttled: true,
+                MeasuredAt: measuredAt, MedianMs: 0.009, MinMs: 0.004, MaxMs: 0.03, Samples: 100),
+            new BenchmarkResult(0.5002, 5002, 0.02, 0.003, Samples: 100)
+        };
+
+        List<BenchmarkResult> parsed = BenchmarkReport.ParseCsv(new BenchmarkReport(results).ToCsv());
+
+        Assert.Equal(results, parsed);
+        Assert.True(parsed[0].Throttled);
+        Assert.False(parsed[1].Throttled);
+    }
+
+    [Fact]
+    public void Optimal_ByMedian_IgnoresMeanInflatedBySpikes()
+    {
+        BenchmarkResult[] results =
+        {
+            new BenchmarkResult(0.5, 5000, 0.02, 0.001, MedianMs: 0.02),
+            new BenchmarkResult(0.5002, 5002, 0.03, 0.01, MedianMs: 0.01)
+        };
+
+        Assert.Equal(5000, new BenchmarkReport(results).Optimal?.Resolution);
+        Assert.Equal(5002, new BenchmarkReport(results, OptimalMetric.Median).Optimal?.Resolution);
+    }
+
+    [Fact]
+    public void TableLines_ByMedian_MeasuresBandOnMedian()
+    {
+        BenchmarkResult[] results =
+        {
+            new BenchmarkResult(0.5, 5000, 0.05, 0.001, MedianMs: 0.011),
+            new BenchmarkResult(0.5002, 5002, 0.03, 0.01, MedianMs: 0.01)
+        };
+
+        List<RowKind> kinds = new BenchmarkReport(results, OptimalMetric.Median)
+            .TableLines(SortOrder.Resolution, 0.005)
+            .Select(line => line.Kind)
+            .ToList();
+
+        Assert.Contains(RowKind.WithinBand, kinds);
+    }
+
+    [Fact]
+    public void ReplaceRefined_DuplicateUnits_TakesTheLastMeasurement()
+    {
+        // --no-clamp snaps several requested resolutions to one unit, so a results file can repeat it
+        BenchmarkResult[] previous =
+        {
+            new BenchmarkResult(0.5, 5000, 0.02, 0.01),
+            new BenchmarkResult(0.5, 5000, 0.03, 0.02),
+            new BenchmarkResult(0.5002, 5002, 0.01, 0.001)
+        };
+        BenchmarkResult[] refined =
+        {
+            new BenchmarkResult(0.5, 5000, 0.015, 0.002),
+            new BenchmarkResult(0.5, 5000, 0.016, 0.003)
+        };
+
+        List<BenchmarkResult> results = BenchmarkReport.ReplaceRefined(previous, refined);
+
+        Assert.Equal(new[] { refined[1], refined[1], previous[2] }, results);
+    }
+
+    [Fact]
+    public void Merge_AveragesDeltasAndPoolsStdev()
+    {
+        List<BenchmarkResult> first = new List<BenchmarkResult> { new BenchmarkResult(0.5, 5000, 0.01, 0.003) };
+        List<BenchmarkResult> second = new List<BenchmarkResult> { new BenchmarkResult(0.5, 5000, 0.03, 0.004) };
+
+        BenchmarkResult merged = Assert.Single(BenchmarkReport.Merge(new[] { first, second }).Results);
+
+        Assert.Equal(0.02, merged.DeltaMs, Tolerance);
+        Assert.Equal(Math.Sqrt((0.003 * 0.003 + 0.0001 + 0.004 * 0.004 + 0.0001) / 2), merged.Stdev, Tolerance);
+    }
+
+    [Fact]
+    public void Merge_WeightsRunsBySampleCount()
+    {
+        List<BenchmarkResult> first = new List<BenchmarkResult> { new BenchmarkResult(0.5, 5000, 0.01, 0.003, Samples: 300) };
+        List<BenchmarkResult> second = new List<BenchmarkResult> { new BenchmarkResult(0.5, 5000, 0.03, 0.004, Samples: 100) };
+
+        BenchmarkResult merged = Assert.Single(BenchmarkReport.Merge(new[] { first, second }).Results);
+
+        Assert.Equal(0.015, merged.DeltaMs, Tolerance);
+        Assert.Equal(Math.Sqrt((300 * (0.003 * 0.003 + 0.000025) + 100 * (0.004 * 0.004 + 0.000225)) / 400), merged.Stdev,
+            Tolerance);
+        Assert.Equal(400, merged.Samples);
+    }
+
+    [Fact]
+    public void Merge_WithoutSampleCounts_WeightsRunsEqually()
+    {
+        List<BenchmarkResult> first = new List<BenchmarkResult> { new BenchmarkResult(0.5, 5000, 0.01, 0.003, Samples: 300) };
+        List<BenchmarkResult> second = new List<BenchmarkResult> { new BenchmarkResult(0.5, 5000, 0.03, 0.004) };
+
+        BenchmarkResult merged = Assert.Single(BenchmarkReport.Merge(new[] { first, second }).Results);
+
+        Assert.Equal(0.02, merged.DeltaMs, Tolerance);
+        Assert.Null(merged.Samples);
+    }
+
+    [Fact]
+    public void Merge_CombinesOptionalColumns()
+    {
+        List<BenchmarkResult> first = new List<BenchmarkResult>
+        {
+            new BenchmarkResult(0.5, 5000, 0.01, 0.003, MedianMs: 0.01, MinMs: 0.005, MaxMs: 0.02)
+        };
+        List<BenchmarkResult> second = new List<BenchmarkResult>
+        {
+            new BenchmarkResult(0.5, 5000, 0.03, 0.004, Throttled: true, MedianMs: 0.02, MinMs: 0.004, MaxMs: 0.05)
+        };
+
+        BenchmarkResult merged = Assert.Single(BenchmarkReport.Merge(new[] { first, second }).Results);
+
+        Assert.Equal(0.015, merged.MedianMs!.Value, Tolerance);
+        Assert.Equal(0.004, merged.MinMs);
+        Assert.Equal(0.05, merged.MaxMs);
+        Assert.True(merged.Throttled);
+        Assert.Null(merged.HighResTimerDeltaMs);
+    }
+}
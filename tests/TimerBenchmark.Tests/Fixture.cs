@@ -0,0 +1,10 @@
+namespace TimerBenchmark.Tests;
+
+internal static class Fixture
+{
+    // Measurement.Parse splits on Environment.NewLine, so normalize whatever line endings the checkout produced
+    public static string Read(string name)
+    {
+        return string.Join(Environment.NewLine, File.ReadAllLines(Path.Combine(AppContext.BaseDirectory, "Fixtures", name)));
+    }
+}
@@ -0,0 +1,146 @@
+using Xunit;
+
+namespace TimerBenchmark.Tests;
+
+public class MeasurementTests
+{
+    private const double Tolerance = 1E-6;
+
+    [Fact]
+    public void Parse_ValidOutput_UsesReportedStatistics()
+    {
+        Measurement measurement = Measurement.Parse(Fixture.Read("measuresleep-valid.txt"), 0);
+
+        Assert.True(measurement.IsValid);
+        Assert.Equal(0.025, measurement.Avg, Tolerance);
+        Assert.Equal(0.0111803, measurement.Stdev, Tolerance);
+        Assert.Equal(new[] { 0.01, 0.02, 0.03, 0.04 }, measurement.Samples);
+    }
+
+    [Fact]
+    public void Parse_WithDiscardedSamples_RecomputesFromRemainingSamples()
+    {
+        Measurement measurement = Measurement.Parse(Fixture.Read("measuresleep-valid.txt"), 1);
+
+        Assert.Equal(new[] { 0.02, 0.03, 0.04 }, measurement.Samples);
+        Assert.Equal(0.03, measurement.Avg, Tolerance);
+        Assert.Equal(Math.Sqrt(2E-4 / 3), measurement.Stdev, Tolerance);
+    }
+
+    [Fact]
+    public void Parse_ZeroOutput_IsInvalid()
+    {
+        Assert.False(Measurement.Parse(Fixture.Read("measuresleep-zeros.txt"), 0).IsValid);
+    }
+
+    [Fact]
+    public void Parse_MalformedOutput_IsInvalid()
+    {
+        Measurement measurement = Measurement.Parse(Fixture.Read("measuresleep-malformed.txt"), 0);
+
+        Assert.False(measurement.IsValid);
+        Assert.Empty(measurement.Samples);
+    }
+
+    [Fact]
+    public void Parse_LocalizedDecimals_ReadsDecimalComma()
+    {
+        Measurement measurement = Measurement.Parse(Fixture.Read("measuresleep-localized.txt"), 0);
+
+        Assert.Equal(0.02, measurement.Avg, Tolerance);
+        Assert.Equal(0.01, measurement.Stdev, Tolerance);
+        Assert.Equal(new[] { 0.01, 0.03 }, measurement.Samples);
+    }
+
+    [Fact]
+    public void Parse_JsonOutput_MatchesTextFormat()
+    {
+        Measurement measurement = Measurement.Parse(Fixture.Read("measuresleep-json.txt"), 1);
+
+        Assert.Equal(new[] { 0.02, 0.03, 0.04 }, measurement.Samples);
+        Assert.Equal(0.03, measurement.Avg, Tolerance);
+    }
+
+    [Fact]
+    public void Parse_BrokenJson_FallsBackToLinePrefixes()
+    {
+        Measurement measurement = Measurement.Parse("{\"avg\": " + Environment.NewLine + "Avg: 0.5" + Environment.NewLine, 0);
+
+        Assert.Equal(0.5, measurement.Avg, Tolerance);
+    }
+
+    [Fact]
+    public void Median_IsMiddleOfSortedSamples()
+    {
+        Measurement measurement = Measurement.FromSamples(new[] { 0.04, 0.01, 0.9, 0.02 });
+
+        Assert.Equal(0.03, measurement.Median!.Value, Tolerance);
+        Assert.Equal(0.01, measurement.Min);
+        Assert.Equal(0.9, measurement.Max);
+    }
+
+    [Fact]
+    public void Trim_DropsHighestAndLowestSamples()
+    {
+        double[] samples = { 0.5, 0.01, 0.02, 0.03, 0.02, 0.01, 0.02, 0.03, 0.02, 0 };
+
+        Measurement trimmed = Measurement.FromSamples(samples).Trim(10);
+
+        Assert.Equal(8, trimmed.Samples.Count);
+        Assert.DoesNotContain(0.5, trimmed.Samples);
+        Assert.DoesNotContain(0.0, trimmed.Samples);
+    }
+
+    [Fact]
+    public void ParseRawSamples_SkipsCommentsAndBlankLines()
+    {
+        Measurement measurement = Measurement.ParseRawSamples(Fixture.Read("raw-samples.txt"));
+
+        Assert.Equal(new[] { 0.01, 0.02, 0.03 }, measurement.Samples);
+        Assert.Equal(0.02, measurement.Avg, Tolerance);
+    }
+
+    [Fact]
+    public void Parse_DiscardWithoutSamples_KeepsReportedStatisticsAndFlagsIt()
+    {
+        string output = $"Avg: 0.02{Environment.NewLine}STDEV: 0.01{Environment.NewLine}";
+
+        Measurement measurement = Measurement.Parse(output, 2);
+
+        Assert.Equal(0.02, measurement.Avg, Tolerance);
+        Assert.True(measurement.DiscardIgnored);
+        Assert.False(Measurement.Parse(output, 0).DiscardIgnored);
+    }
+
+    [Fact]
+    public void ParseRawSamples_InvalidLine_ReportsLineNumber()
+    {
+        FormatException e = Assert.Throws<FormatException>(() => Measurement.ParseRawSamples("0.01\nabc\n"));
+
+        Assert.Contains("line 2", e.Message);
+    }
+
+    [Fact]
+    public void FromSamples_Empty_IsInvalid()
+    {
+        Assert.False(Measurement.FromSamples(Array.Empty<double>()).IsValid);
+    }
+
+    [Theory]
+    [InlineData("First", 0.3)]
+    [InlineData("Best", 0.1)]
+    [InlineData("Median", 0.2)]
+    public void Aggregate_PicksMeasurementByPolicy(string policy, double expectedAvg)
+    {
+        // RetryAggregation is internal, so a public theory cannot take it as a parameter
+        RetryAggregation aggregation = Enum.Parse<RetryAggregation>(policy);
+        List<Measurement> measurements = new List<Measurement>
+        {
+            Measurement.FromSamples(new[] { 0.3 }),
+            Measurement.FromSamples(new[] { 0.1 }),
+            Measurement.FromSamples(new[] { 0.2 })
+        };
+
+        Assert.Equal(expectedAvg, Measurement.Aggregate(measurements, aggregation).Avg, Tolerance);
+    }
+}
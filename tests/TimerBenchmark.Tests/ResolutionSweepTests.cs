@@ -0,0 +1,129 @@
+using Xunit;
+
+namespace TimerBenchmark.Tests;
+
+public class ResolutionSweepTests
+{
+    private const double Tolerance = 1E-9;
+
+    [Fact]
+    public void Values_NonDivisibleRange_ShortensLastStepToEndValue()
+    {
+        List<double> values = ResolutionSweep.Values(Range(0.5, 0.003, 0.51), out string? warning);
+
+        Assert.Equal(new[] { 0.5, 0.503, 0.506, 0.509, 0.51 }, values, new ToleranceComparer());
+        Assert.Contains("does not divide", warning);
+    }
+
+    [Fact]
+    public void Values_DivisibleRange_EndsAtEndValueWithoutExtraStep()
+    {
+        // (0.6 - 0.5) / 0.001 is 99.99999999999997 in binary, which must still count as 100 whole steps
+        List<double> values = ResolutionSweep.Values(Range(0.5, 0.001, 0.6), out string? warning);
+
+        Assert.Null(warning);
+        Assert.Equal(101, values.Count);
+        Assert.Equal(0.6, values[^1], Tolerance);
+    }
+
+    [Fact]
+    public void Values_IncrementLargerThanRange_TestsBothEnds()
+    {
+        List<double> values = ResolutionSweep.Values(Range(0.5, 0.05, 0.51), out string? warning);
+
+        Assert.Equal(new[] { 0.5, 0.51 }, values);
+        Assert.Contains("is larger than the range", warning);
+    }
+
+    [Theory]
+    [InlineData(0.51)]
+    [InlineData(0.5)]
+    public void RangeError_EndNotAboveStart_IsReported(double start)
+    {
+        Assert.Contains("EndValue must be greater than StartValue", ResolutionSweep.RangeError(Range(start, 0.001, 0.5)));
+    }
+
+    [Theory]
+    [InlineData(0)]
+    [InlineData(-0.001)]
+    public void RangeError_IncrementNotPositive_IsReported(double increment)
+    {
+        Assert.Contains("IncrementValue must be positive", ResolutionSweep.RangeError(Range(0.5, increment, 0.6)));
+    }
+
+    [Fact]
+    public void RangeError_ValidRange_IsNull()
+    {
+        Assert.Null(ResolutionSweep.RangeError(Range(0.5, 0.001, 0.6)));
+    }
+
+    [Fact]
+    public void ListError_NonPositiveValue_IsReported()
+    {
+        Assert.Null(ResolutionSweep.ListError(new[] { 0.5, 1 }));
+        Assert.NotNull(ResolutionSweep.ListError(new[] { 0.5, 0 }));
+        Assert.NotNull(ResolutionSweep.ListError(new[] { -0.5 }));
+    }
+
+    [Theory]
+    [InlineData("Nearest", 5030)]
+    [InlineData("Floor", 5029)]
+    [InlineData("Ceil", 5030)]
+    public void ToUnits_Midpoint_FollowsRoundingMode(string mode, int expected)
+    {
+        // RoundingMode is internal, so a public theory cannot take it as a parameter
+        Assert.Equal(expected, ResolutionSweep.ToUnits(0.50295, Enum.Parse<RoundingMode>(mode)));
+    }
+
+    [Theory]
+    [InlineData("Nearest")]
+    [InlineData("Floor")]
+    [InlineData("Ceil")]
+    public void ToUnits_BinaryNoise_IsIgnoredByEveryRoundingMode(string mode)
+    {
+        Assert.Equal(5029, ResolutionSweep.ToUnits(0.5029, Enum.Parse<RoundingMode>(mode)));
+    }
+
+    [Fact]
+    public void AdaptiveValues_OptimalOnTheEdge_RefinesInsideTheRange()
+    {
+        BenchmarkingParameters parameters = Range(0.5, 0.05, 0.6);
+        List<BenchmarkResult> results = new List<BenchmarkResult>();
+
+        // The sweep loop adds a row per value before asking for the next one, with the best delta at StartValue
+        List<double> coarseValues = ResolutionSweep.Values(parameters, out _);
+        foreach (double value in ResolutionSweep.AdaptiveValues(coarseValues, parameters, 0.002, results,
+                     RoundingMode.Nearest, OptimalMetric.Mean))
+        {
+            int units = ResolutionSweep.ToUnits(value, RoundingMode.Nearest);
+            results.Add(new BenchmarkResult(units / 1E4, units, Math.Abs(value - 0.5), 0.001));
+        }
+
+        Assert.True(results.Count > 3);
+        Assert.All(results, r => Assert.InRange(r.ResolutionMs, 0.5, 0.6));
+        Assert.Contains(results, r => r.Resolution == 5020);
+        Assert.Equal(results.Count, results.Select(r => r.Resolution).Distinct().Count());
+    }
+
+    [Theory]
+    [InlineData(0.05, 0.002, 11 + 11)]
+    [InlineData(0.05, 0.004, 11 + 7)]
+    [InlineData(0.05, 0.05, 0)]
+    public void AdaptiveProbeCount_MatchesRefinementWindows(double increment, double finest, int expected)
+    {
+        // 0.05 -> 0.01 probes ±5 steps; 0.01 -> 0.004 is clamped and probes ±ceil(2.5) steps
+        Assert.Equal(expected, ResolutionSweep.AdaptiveProbeCount(increment, finest));
+    }
+
+    private static BenchmarkingParameters Range(double start, double increment, double end)
+    {
+        return new BenchmarkingParameters { StartValue = start, IncrementValue = increment, EndValue = end, SampleValue = 10 };
+    }
+
+    private sealed class ToleranceComparer : IEqualityComparer<double>
+    {
+        public bool Equals(double x, double y) => Math.Abs(x - y) < Tolerance;
+
+        public int GetHashCode(double obj) => 0;
+    }
+}